@@ -3,10 +3,14 @@
 use pyo3::prelude::*;
 use pyo3::exceptions::PyValueError;
 use ndarray::Array2;
+#[cfg(feature = "numpy")]
+use ndarray::{ArrayViewD, IxDyn};
+#[cfg(feature = "numpy")]
+use numpy::{PyArrayDyn, PyReadonlyArrayDyn, ToPyArray};
 use rayon::prelude::*;
 
 #[pyclass]
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct NDArray {
     data: Vec<f64>,
     dims: Vec<usize>,
@@ -51,6 +55,34 @@ impl NDArray {
         self.data.clone()
     }
 
+    /// Create an NDArray from a NumPy array of float64, keeping its shape.
+    /// A C-contiguous array is copied in one block, without going through a
+    /// Python list; any other layout is copied element by element. The
+    /// result owns its data, so later changes to `array` don't affect it.
+    #[cfg(feature = "numpy")]
+    #[staticmethod]
+    fn from_numpy(array: PyReadonlyArrayDyn<f64>) -> PyResult<Self> {
+        let dims = array.shape().to_vec();
+        if dims.is_empty() {
+            return Err(PyValueError::new_err("Dimensions cannot be empty"));
+        }
+        
+        let data = match array.as_slice() {
+            Ok(slice) => slice.to_vec(),
+            Err(_) => array.as_array().iter().copied().collect(),
+        };
+        Ok(Self::with_dims(data, dims))
+    }
+
+    /// Convert to a NumPy array of the same shape, copied straight from the
+    /// buffer
+    #[cfg(feature = "numpy")]
+    fn to_numpy<'py>(&self, py: Python<'py>) -> PyResult<&'py PyArrayDyn<f64>> {
+        let view = ArrayViewD::from_shape(IxDyn(&self.dims), &self.data)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(view.to_pyarray(py))
+    }
+
     fn get(&self, indices: Vec<usize>) -> PyResult<f64> {
         let flat_idx = self.compute_flat_index(&indices)?;
         Ok(self.data[flat_idx])
@@ -70,40 +102,50 @@ impl NDArray {
         self.data.len()
     }
 
-    /// Element-wise addition
+    /// Permute the axes, reversing them when `axes` is not given. The
+    /// result is a new contiguous array.
+    #[pyo3(signature = (axes=None))]
+    fn transpose(&self, axes: Option<Vec<usize>>) -> PyResult<NDArray> {
+        self.transposed(axes).map_err(PyValueError::new_err)
+    }
+
+    /// The same elements in a new shape with the same total size
+    fn reshape(&self, dims: Vec<usize>) -> PyResult<NDArray> {
+        self.reshaped(dims).map_err(PyValueError::new_err)
+    }
+
+    /// Element-wise addition, broadcasting the shapes as NumPy does
     fn add(&self, other: &NDArray) -> PyResult<NDArray> {
-        if self.dims != other.dims {
-            return Err(PyValueError::new_err("Shapes must match for addition"));
-        }
-        
-        let result: Vec<f64> = self.data.par_iter()
-            .zip(other.data.par_iter())
-            .map(|(&a, &b)| a + b)
-            .collect();
-        
-        Ok(NDArray {
-            data: result,
-            dims: self.dims.clone(),
-            strides: self.strides.clone(),
-        })
+        self.broadcast_with(other, |a, b| a + b).map_err(PyValueError::new_err)
     }
 
-    /// Element-wise multiplication
+    /// Element-wise multiplication, broadcasting the shapes as NumPy does
     fn mul(&self, other: &NDArray) -> PyResult<NDArray> {
-        if self.dims != other.dims {
-            return Err(PyValueError::new_err("Shapes must match for multiplication"));
+        self.broadcast_with(other, |a, b| a * b).map_err(PyValueError::new_err)
+    }
+
+    /// Matrix product of two rank-2 arrays
+    fn matmul(&self, other: &NDArray) -> PyResult<NDArray> {
+        if self.dims.len() != 2 || other.dims.len() != 2 {
+            return Err(PyValueError::new_err(
+                format!("matmul needs two 2-D arrays, got shapes {:?} and {:?}", self.dims, other.dims)
+            ));
+        }
+        if self.dims[1] != other.dims[0] {
+            return Err(PyValueError::new_err(
+                format!("Cannot multiply shapes {:?} and {:?}: {} columns but {} rows",
+                    self.dims, other.dims, self.dims[1], other.dims[0])
+            ));
         }
         
-        let result: Vec<f64> = self.data.par_iter()
-            .zip(other.data.par_iter())
-            .map(|(&a, &b)| a * b)
-            .collect();
+        let a = Array2::from_shape_vec((self.dims[0], self.dims[1]), self.data.clone())
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let b = Array2::from_shape_vec((other.dims[0], other.dims[1]), other.data.clone())
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let product = a.dot(&b);
         
-        Ok(NDArray {
-            data: result,
-            dims: self.dims.clone(),
-            strides: self.strides.clone(),
-        })
+        let dims = vec![self.dims[0], other.dims[1]];
+        Ok(Self::with_dims(product.into_iter().collect(), dims))
     }
 
     /// Scalar multiplication
@@ -128,9 +170,204 @@ impl NDArray {
     fn mean(&self) -> f64 {
         self.sum() / self.data.len() as f64
     }
+
+    /// Smallest element
+    fn min(&self) -> PyResult<f64> {
+        self.require_elements("min").map_err(PyValueError::new_err)?;
+        Ok(self.data.par_iter().copied().reduce(|| f64::INFINITY, f64::min))
+    }
+
+    /// Largest element
+    fn max(&self) -> PyResult<f64> {
+        self.require_elements("max").map_err(PyValueError::new_err)?;
+        Ok(self.data.par_iter().copied().reduce(|| f64::NEG_INFINITY, f64::max))
+    }
+
+    /// Flat index of the largest element (the first one, on ties)
+    fn argmax(&self) -> PyResult<usize> {
+        self.largest_index().map_err(PyValueError::new_err)
+    }
+
+    /// Variance: the population variance by default, or with `ddof=1` the
+    /// sample variance
+    #[pyo3(signature = (ddof=0))]
+    fn var(&self, ddof: usize) -> PyResult<f64> {
+        self.variance(ddof).map_err(PyValueError::new_err)
+    }
+
+    /// Standard deviation, the square root of `var(ddof)`
+    #[pyo3(signature = (ddof=0))]
+    fn std(&self, ddof: usize) -> PyResult<f64> {
+        Ok(self.var(ddof)?.sqrt())
+    }
+
+    /// Sum along `axis`, dropping that dimension (a 1-D array reduces to
+    /// shape `[1]`)
+    fn sum_axis(&self, axis: usize) -> PyResult<NDArray> {
+        self.summed_along(axis).map_err(PyValueError::new_err)
+    }
+
+    /// Mean along `axis`, dropping that dimension
+    fn mean_axis(&self, axis: usize) -> PyResult<NDArray> {
+        let sums = self.summed_along(axis).map_err(PyValueError::new_err)?;
+        Ok(sums.scale(1.0 / self.dims[axis] as f64))
+    }
 }
 
 impl NDArray {
+    /// A contiguous array over `data`, which must hold `dims`' elements
+    fn with_dims(data: Vec<f64>, dims: Vec<usize>) -> NDArray {
+        let strides = Self::compute_strides(&dims);
+        NDArray { data, dims, strides }
+    }
+
+    fn transposed(&self, axes: Option<Vec<usize>>) -> Result<NDArray, String> {
+        let rank = self.dims.len();
+        let axes = axes.unwrap_or_else(|| (0..rank).rev().collect());
+        let mut sorted = axes.clone();
+        sorted.sort_unstable();
+        if sorted != (0..rank).collect::<Vec<_>>() {
+            return Err(format!("Axes {:?} are not a permutation of the {} dimensions", axes, rank));
+        }
+        
+        let dims: Vec<usize> = axes.iter().map(|&axis| self.dims[axis]).collect();
+        let source_strides: Vec<usize> = axes.iter().map(|&axis| self.strides[axis]).collect();
+        let data: Vec<f64> = (0..self.data.len()).into_par_iter()
+            .map(|index| {
+                // Walk the output index back into the source layout
+                let mut rest = index;
+                let mut offset = 0;
+                for (dim, stride) in dims.iter().zip(&source_strides).rev() {
+                    offset += (rest % dim) * stride;
+                    rest /= dim;
+                }
+                self.data[offset]
+            })
+            .collect();
+        
+        Ok(Self::with_dims(data, dims))
+    }
+
+    fn reshaped(&self, dims: Vec<usize>) -> Result<NDArray, String> {
+        if dims.is_empty() {
+            return Err("Dimensions cannot be empty".to_string());
+        }
+        let size: usize = dims.iter().product();
+        if size != self.data.len() {
+            return Err(format!("Cannot reshape array of size {} into shape {:?}", self.data.len(), dims));
+        }
+        
+        Ok(Self::with_dims(self.data.clone(), dims))
+    }
+
+    fn summed_along(&self, axis: usize) -> Result<NDArray, String> {
+        if axis >= self.dims.len() {
+            return Err(format!("Axis {} is out of bounds for an array with {} dimensions", axis, self.dims.len()));
+        }
+        
+        let length = self.dims[axis];
+        let inner = self.strides[axis];
+        let outer: usize = self.dims[..axis].iter().product();
+        let data = (0..outer * inner).into_par_iter()
+            .map(|index| {
+                let start = (index / inner) * length * inner + index % inner;
+                (0..length).map(|step| self.data[start + step * inner]).sum()
+            })
+            .collect();
+        
+        let mut dims = self.dims.clone();
+        dims.remove(axis);
+        if dims.is_empty() {
+            dims.push(1);
+        }
+        Ok(Self::with_dims(data, dims))
+    }
+
+    fn require_elements(&self, operation: &str) -> Result<(), String> {
+        if self.data.is_empty() {
+            return Err(format!("Cannot take the {} of an empty array", operation));
+        }
+        Ok(())
+    }
+
+    fn largest_index(&self) -> Result<usize, String> {
+        self.require_elements("argmax")?;
+        let (index, _) = self.data.par_iter().copied().enumerate()
+            .reduce(|| (usize::MAX, f64::NEG_INFINITY), |a, b| {
+                if b.1 > a.1 || (b.1 == a.1 && b.0 < a.0) { b } else { a }
+            });
+        // Only NaNs never compare greater; report the first element then
+        Ok(if index == usize::MAX { 0 } else { index })
+    }
+
+    fn variance(&self, ddof: usize) -> Result<f64, String> {
+        self.require_elements("var")?;
+        if ddof >= self.size() {
+            return Err(format!("ddof {} must be smaller than the array size {}", ddof, self.size()));
+        }
+        let mean = self.mean();
+        let squares: f64 = self.data.par_iter().map(|&x| (x - mean) * (x - mean)).sum();
+        Ok(squares / (self.size() - ddof) as f64)
+    }
+
+    /// Applies `op` element-wise over the two arrays broadcast together:
+    /// shapes are aligned from the trailing dimension, and a dimension of
+    /// size 1 (or a missing one) stretches to match the other.
+    fn broadcast_with(&self, other: &NDArray, op: fn(f64, f64) -> f64) -> Result<NDArray, String> {
+        if self.dims == other.dims {
+            let data = self.data.par_iter()
+                .zip(other.data.par_iter())
+                .map(|(&a, &b)| op(a, b))
+                .collect();
+            return Ok(Self::with_dims(data, self.dims.clone()));
+        }
+        
+        let rank = self.dims.len().max(other.dims.len());
+        let incompatible = || format!("Shapes {:?} and {:?} cannot be broadcast together", self.dims, other.dims);
+        let mut dims = vec![0; rank];
+        for (i, dim) in dims.iter_mut().enumerate() {
+            let (a, b) = (self.broadcast_dim(rank, i), other.broadcast_dim(rank, i));
+            *dim = match (a, b) {
+                _ if a == b => a,
+                (1, _) => b,
+                (_, 1) => a,
+                _ => return Err(incompatible()),
+            };
+        }
+        
+        let (a_strides, b_strides) = (self.broadcast_strides(rank), other.broadcast_strides(rank));
+        let size: usize = dims.iter().product();
+        let data = (0..size).into_par_iter()
+            .map(|index| {
+                let (mut rest, mut a_offset, mut b_offset) = (index, 0, 0);
+                for i in (0..rank).rev() {
+                    let position = rest % dims[i];
+                    rest /= dims[i];
+                    a_offset += position * a_strides[i];
+                    b_offset += position * b_strides[i];
+                }
+                op(self.data[a_offset], other.data[b_offset])
+            })
+            .collect();
+        
+        Ok(Self::with_dims(data, dims))
+    }
+
+    /// Size of dimension `i` once the shape is padded on the left to `rank`
+    fn broadcast_dim(&self, rank: usize, i: usize) -> usize {
+        let padding = rank - self.dims.len();
+        if i < padding { 1 } else { self.dims[i - padding] }
+    }
+
+    /// Strides for reading this array as if it had the broadcast shape:
+    /// stretched dimensions repeat their element with a stride of 0
+    fn broadcast_strides(&self, rank: usize) -> Vec<usize> {
+        let padding = rank - self.dims.len();
+        (0..rank).map(|i| {
+            if i < padding || self.dims[i - padding] == 1 { 0 } else { self.strides[i - padding] }
+        }).collect()
+    }
+
     fn compute_strides(dims: &[usize]) -> Vec<usize> {
         let mut strides = vec![1; dims.len()];
         for i in (0..dims.len()-1).rev() {
@@ -163,10 +400,13 @@ impl NDArray {
 fn aslang_array_ops(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<NDArray>()?;
     m.add_function(wrap_pyfunction!(parallel_map, m)?)?;
+    m.add_function(wrap_pyfunction!(parallel_apply, m)?)?;
     m.add_function(wrap_pyfunction!(parallel_reduce, m)?)?;
     m.add_function(wrap_pyfunction!(matrix_multiply, m)?)?;
     m.add_function(wrap_pyfunction!(elementwise_add, m)?)?;
     m.add_function(wrap_pyfunction!(elementwise_mul, m)?)?;
+    m.add_function(wrap_pyfunction!(elementwise_sub, m)?)?;
+    m.add_function(wrap_pyfunction!(elementwise_div, m)?)?;
     Ok(())
 }
 
@@ -178,6 +418,39 @@ fn parallel_map(input: Vec<f64>, scale: f64) -> PyResult<Vec<f64>> {
     Ok(result)
 }
 
+/// Elements handed to `parallel_apply`'s callable per GIL acquisition
+const APPLY_CHUNK: usize = 1024;
+
+/// Apply a Python callable to every element. Calls into Python still run
+/// one at a time under the GIL: the input is split into chunks that rayon
+/// workers take in turn, re-acquiring the GIL per chunk, so `func` itself
+/// never runs in parallel. If `func` raises, chunks not yet started are
+/// skipped and an exception is propagated: the first to be raised, which
+/// is not necessarily the one for the lowest index.
+#[pyfunction]
+fn parallel_apply(py: Python, input: Vec<f64>, func: PyObject) -> PyResult<Vec<f64>> {
+    py.allow_threads(|| {
+        apply_in_chunks(&input, |chunk| Python::with_gil(|py| {
+            chunk.iter()
+                .map(|&x| func.call1(py, (x,))?.extract::<f64>(py))
+                .collect()
+        }))
+    })
+}
+
+/// Runs `apply` over `APPLY_CHUNK`-sized pieces of `input` in parallel and
+/// joins the results in order, stopping at the first error.
+fn apply_in_chunks<E, F>(input: &[f64], apply: F) -> Result<Vec<f64>, E>
+where
+    E: Send,
+    F: Fn(&[f64]) -> Result<Vec<f64>, E> + Sync + Send,
+{
+    let chunks: Vec<Vec<f64>> = input.par_chunks(APPLY_CHUNK)
+        .map(apply)
+        .collect::<Result<_, E>>()?;
+    Ok(chunks.concat())
+}
+
 #[pyfunction]
 fn parallel_reduce(input: Vec<f64>) -> PyResult<f64> {
     let sum: f64 = input.par_iter().sum();
@@ -208,6 +481,35 @@ fn elementwise_mul(a: Vec<f64>, b: Vec<f64>) -> PyResult<Vec<f64>> {
     Ok(result)
 }
 
+#[pyfunction]
+fn elementwise_sub(a: Vec<f64>, b: Vec<f64>) -> PyResult<Vec<f64>> {
+    if a.len() != b.len() {
+        return Err(PyValueError::new_err("Vectors must have same length"));
+    }
+    let result: Vec<f64> = a.par_iter()
+        .zip(b.par_iter())
+        .map(|(&x, &y)| x - y)
+        .collect();
+    Ok(result)
+}
+
+/// Element-wise division. A zero divisor raises `ValueError` rather than
+/// producing inf or NaN.
+#[pyfunction]
+fn elementwise_div(a: Vec<f64>, b: Vec<f64>) -> PyResult<Vec<f64>> {
+    if a.len() != b.len() {
+        return Err(PyValueError::new_err("Vectors must have same length"));
+    }
+    if let Some(index) = b.par_iter().position_first(|&y| y == 0.0) {
+        return Err(PyValueError::new_err(format!("Division by zero at index {}", index)));
+    }
+    let result: Vec<f64> = a.par_iter()
+        .zip(b.par_iter())
+        .map(|(&x, &y)| x / y)
+        .collect();
+    Ok(result)
+}
+
 #[pyfunction]
 fn matrix_multiply(a: Vec<Vec<f64>>, b: Vec<Vec<f64>>) -> PyResult<Vec<Vec<f64>>> {
     if a.is_empty() || b.is_empty() || a[0].len() != b.len() {
@@ -230,4 +532,187 @@ fn matrix_multiply(a: Vec<Vec<f64>>, b: Vec<Vec<f64>>) -> PyResult<Vec<Vec<f64>>
     Ok(result.outer_iter()
         .map(|row| row.to_vec())
         .collect())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // These call the helpers behind the Python methods: building a `PyErr`
+    // would need libpython, which an extension module doesn't link.
+
+    fn array(data: Vec<f64>, dims: Vec<usize>) -> NDArray {
+        assert_eq!(data.len(), dims.iter().product::<usize>());
+        NDArray::with_dims(data, dims)
+    }
+
+    fn range(dims: Vec<usize>) -> NDArray {
+        let size = dims.iter().product::<usize>();
+        array((0..size).map(|x| x as f64).collect(), dims)
+    }
+
+    #[test]
+    fn test_transpose_reverses_axes_by_default() {
+        let matrix = range(vec![2, 3]);
+        let transposed = matrix.transposed(None).unwrap();
+        assert_eq!(transposed.dims, vec![3, 2]);
+        assert_eq!(transposed.strides, vec![2, 1]);
+        assert_eq!(transposed.data, vec![0.0, 3.0, 1.0, 4.0, 2.0, 5.0]);
+        assert_eq!(transposed.transposed(None).unwrap().data, matrix.data);
+    }
+
+    #[test]
+    fn test_transpose_follows_given_axes() {
+        let cube = range(vec![2, 3, 4]);
+        let moved = cube.transposed(Some(vec![1, 2, 0])).unwrap();
+        assert_eq!(moved.dims, vec![3, 4, 2]);
+        for (i, j, k) in (0..3).flat_map(|i| (0..4).flat_map(move |j| (0..2).map(move |k| (i, j, k)))) {
+            assert_eq!(moved.data[i * 8 + j * 2 + k], cube.data[k * 12 + i * 4 + j]);
+        }
+        assert_eq!(cube.transposed(Some(vec![0, 1, 2])).unwrap().data, cube.data);
+    }
+
+    #[test]
+    fn test_transpose_rejects_non_permutations() {
+        let cube = range(vec![2, 3, 4]);
+        assert_eq!(cube.transposed(Some(vec![0, 0, 1])).unwrap_err(),
+            "Axes [0, 0, 1] are not a permutation of the 3 dimensions");
+        assert!(cube.transposed(Some(vec![0, 1])).is_err());
+        assert!(cube.transposed(Some(vec![0, 1, 3])).is_err());
+    }
+
+    #[test]
+    fn test_reshape_keeps_element_order() {
+        let reshaped = range(vec![2, 3]).reshaped(vec![3, 1, 2]).unwrap();
+        assert_eq!(reshaped.dims, vec![3, 1, 2]);
+        assert_eq!(reshaped.strides, vec![2, 2, 1]);
+        assert_eq!(reshaped.data, range(vec![6]).data);
+    }
+
+    #[test]
+    fn test_reshape_rejects_size_mismatch() {
+        let matrix = range(vec![2, 3]);
+        assert_eq!(matrix.reshaped(vec![4, 2]).unwrap_err(), "Cannot reshape array of size 6 into shape [4, 2]");
+        assert_eq!(matrix.reshaped(Vec::new()).unwrap_err(), "Dimensions cannot be empty");
+    }
+
+    #[test]
+    fn test_sum_along_each_axis() {
+        let cube = range(vec![2, 3, 4]);
+        let first = cube.summed_along(0).unwrap();
+        assert_eq!(first.dims, vec![3, 4]);
+        assert_eq!(first.data, (0..12).map(|i| (2 * i + 12) as f64).collect::<Vec<_>>());
+        let middle = cube.summed_along(1).unwrap();
+        assert_eq!(middle.dims, vec![2, 4]);
+        assert_eq!(middle.data, vec![12.0, 15.0, 18.0, 21.0, 48.0, 51.0, 54.0, 57.0]);
+        let last = cube.summed_along(2).unwrap();
+        assert_eq!(last.dims, vec![2, 3]);
+        assert_eq!(last.data, vec![6.0, 22.0, 38.0, 54.0, 70.0, 86.0]);
+    }
+
+    #[test]
+    fn test_sum_along_only_axis_collapses_to_one_element() {
+        let summed = array(vec![1.0, 2.0, 3.5], vec![3]).summed_along(0).unwrap();
+        assert_eq!(summed.dims, vec![1]);
+        assert_eq!(summed.data, vec![6.5]);
+    }
+
+    #[test]
+    fn test_sum_along_rejects_missing_axis() {
+        assert_eq!(range(vec![2, 3]).summed_along(2).unwrap_err(),
+            "Axis 2 is out of bounds for an array with 2 dimensions");
+    }
+
+    #[test]
+    fn test_argmax_takes_first_of_ties() {
+        assert_eq!(array(vec![1.0, 5.0, 5.0, 2.0], vec![4]).largest_index(), Ok(1));
+        // Spread over many rayon splits
+        let mut flat = vec![1.0; 100_000];
+        flat[70_000] = 2.0;
+        flat[90_000] = 2.0;
+        assert_eq!(array(flat, vec![100_000]).largest_index(), Ok(70_000));
+        assert_eq!(array(vec![-3.0; 50_000], vec![50_000]).largest_index(), Ok(0));
+    }
+
+    #[test]
+    fn test_argmax_skips_nan() {
+        assert_eq!(array(vec![f64::NAN, 1.0, f64::NAN, 3.0], vec![4]).largest_index(), Ok(3));
+        assert_eq!(array(vec![f64::NAN; 3], vec![3]).largest_index(), Ok(0));
+        assert_eq!(array(vec![f64::NEG_INFINITY, f64::NAN], vec![2]).largest_index(), Ok(0));
+    }
+
+    #[test]
+    fn test_variance_with_ddof() {
+        let values = array(vec![2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], vec![8]);
+        assert_eq!(values.variance(0), Ok(4.0));
+        assert_eq!(values.variance(1), Ok(32.0 / 7.0));
+        assert_eq!(values.variance(8).unwrap_err(), "ddof 8 must be smaller than the array size 8");
+        assert_eq!(array(vec![3.0], vec![1]).variance(0), Ok(0.0));
+    }
+
+    #[test]
+    fn test_statistics_reject_empty_arrays() {
+        let empty = array(Vec::new(), vec![0]);
+        assert_eq!(empty.largest_index().unwrap_err(), "Cannot take the argmax of an empty array");
+        assert_eq!(empty.variance(0).unwrap_err(), "Cannot take the var of an empty array");
+        assert_eq!(empty.require_elements("min").unwrap_err(), "Cannot take the min of an empty array");
+    }
+
+    #[test]
+    fn test_apply_in_chunks_keeps_order() {
+        let input: Vec<f64> = (0..APPLY_CHUNK * 3 + 7).map(|x| x as f64).collect();
+        let applied = apply_in_chunks(&input, |chunk| Ok::<_, String>(chunk.iter().map(|x| x * 2.0).collect()));
+        assert_eq!(applied.unwrap(), input.iter().map(|x| x * 2.0).collect::<Vec<_>>());
+        assert_eq!(apply_in_chunks(&[], |_| Err("never called".to_string())), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_apply_in_chunks_propagates_errors() {
+        let input: Vec<f64> = (0..APPLY_CHUNK * 4).map(|x| x as f64).collect();
+        let applied = apply_in_chunks(&input, |chunk| match chunk.iter().find(|&&x| x == 2000.0) {
+            Some(x) => Err(format!("bad element {}", x)),
+            None => Ok(chunk.to_vec()),
+        });
+        assert_eq!(applied.unwrap_err(), "bad element 2000");
+    }
+
+    #[test]
+    fn test_broadcast_column_against_row() {
+        let column = array(vec![10.0, 20.0, 30.0], vec![3, 1]);
+        let row = array(vec![1.0, 2.0, 3.0, 4.0], vec![4]);
+        let sum = column.broadcast_with(&row, |a, b| a + b).unwrap();
+        assert_eq!(sum.dims, vec![3, 4]);
+        assert_eq!(sum.strides, vec![4, 1]);
+        assert_eq!(sum.data, vec![
+            11.0, 12.0, 13.0, 14.0,
+            21.0, 22.0, 23.0, 24.0,
+            31.0, 32.0, 33.0, 34.0,
+        ]);
+        // The operands keep their sides
+        let difference = row.broadcast_with(&column, |a, b| a - b).unwrap();
+        assert_eq!(difference.data[..4], [-9.0, -8.0, -7.0, -6.0]);
+    }
+
+    #[test]
+    fn test_broadcast_pads_lower_rank_on_the_left() {
+        let cube = range(vec![2, 2, 3]);
+        let row = array(vec![100.0, 200.0, 300.0], vec![3]);
+        assert_eq!(row.broadcast_strides(3), vec![0, 0, 1]);
+        let sum = cube.broadcast_with(&row, |a, b| a + b).unwrap();
+        assert_eq!(sum.dims, vec![2, 2, 3]);
+        assert_eq!(sum.data, (0..12).map(|i| i as f64 + 100.0 * (i % 3 + 1) as f64).collect::<Vec<_>>());
+        
+        let scalar = array(vec![2.0], vec![1]);
+        assert_eq!(cube.broadcast_with(&scalar, |a, b| a * b).unwrap().data,
+            (0..12).map(|i| i as f64 * 2.0).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_broadcast_rejects_incompatible_shapes() {
+        let a = range(vec![3]);
+        let b = range(vec![4]);
+        assert_eq!(a.broadcast_with(&b, |a, b| a + b).unwrap_err(),
+            "Shapes [3] and [4] cannot be broadcast together");
+        assert!(range(vec![2, 3]).broadcast_with(&range(vec![3, 3]), |a, b| a + b).is_err());
+    }
+}
@@ -2,6 +2,43 @@
 
 use wasm_bindgen::prelude::*;
 use serde::{Serialize, Deserialize};
+use aslang::error::{ASError, ErrorKind, SourceLocation};
+
+fn input_error(message: &str) -> ASError {
+    ASError::new(ErrorKind::IOError, format!("Could not read input: {}", message), SourceLocation::new(0, 0))
+}
+
+/// Passes program output to `emit` one line at a time.
+struct LineWriter<F> {
+    emit: F,
+    pending: String,
+}
+
+impl<F: FnMut(&str) -> std::io::Result<()>> LineWriter<F> {
+    fn new(emit: F) -> Self {
+        LineWriter { emit, pending: String::new() }
+    }
+}
+
+impl<F: FnMut(&str) -> std::io::Result<()>> std::io::Write for LineWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.pending.push_str(&String::from_utf8_lossy(buf));
+        while let Some(end) = self.pending.find('\n') {
+            let line: String = self.pending.drain(..=end).collect();
+            (self.emit)(&line)?;
+        }
+        Ok(buf.len())
+    }
+
+    /// Sends a partial line, such as an `input` prompt.
+    fn flush(&mut self) -> std::io::Result<()> {
+        if !self.pending.is_empty() {
+            let text = std::mem::take(&mut self.pending);
+            (self.emit)(&text)?;
+        }
+        Ok(())
+    }
+}
 
 /// WASM runtime wrapper for AS Lang
 #[wasm_bindgen]
@@ -9,13 +46,20 @@ pub struct WasmRuntime {
     inner: aslang::runtime::Runtime,
 }
 
+impl Default for WasmRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl WasmRuntime {
     #[wasm_bindgen(constructor)]
     pub fn new() -> WasmRuntime {
-        WasmRuntime {
-            inner: aslang::runtime::Runtime::new(),
-        }
+        let mut inner = aslang::runtime::Runtime::new();
+        // There is no stdin in the browser, so input fails until a handler is set
+        inner.set_input_handler(|_| Err(input_error("no input handler set")));
+        WasmRuntime { inner }
     }
 
     /// Execute AS Lang code and return the output as a string
@@ -24,6 +68,39 @@ impl WasmRuntime {
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
+    /// Answer `input` statements by calling `handler` with the prompt ("" when
+    /// there is none). It must return the line as a string; returning null or
+    /// undefined means no input is left. Without a handler, `input` is an
+    /// error rather than blocking.
+    pub fn set_input_handler(&mut self, handler: js_sys::Function) {
+        self.inner.set_input_handler(move |prompt| {
+            let line = handler.call1(&JsValue::NULL, &JsValue::from_str(prompt))
+                .map_err(|e| input_error(&e.as_string().unwrap_or_else(|| format!("{:?}", e))))?;
+            if line.is_null() || line.is_undefined() {
+                return Err(input_error("end of input"));
+            }
+            line.as_string().ok_or_else(|| input_error("input handler must return a string"))
+        });
+    }
+
+    /// Call `handler` with each line of output as it is produced, newline
+    /// included, instead of only returning it all from `execute`. An
+    /// `input` prompt is passed on its own, without a newline. `execute`
+    /// still returns the accumulated text.
+    pub fn set_output_handler(&mut self, handler: js_sys::Function) {
+        self.inner.set_output_writer(LineWriter::new(move |text: &str| {
+            handler.call1(&JsValue::NULL, &JsValue::from_str(text))
+                .map(|_| ())
+                .map_err(|e| std::io::Error::other(e.as_string().unwrap_or_else(|| format!("{:?}", e))))
+        }));
+    }
+
+    /// Make `execute` fail after `limit` opcodes, so a runaway loop can't
+    /// freeze the page; pass undefined to remove the limit.
+    pub fn set_instruction_limit(&mut self, limit: Option<u64>) {
+        self.inner.set_instruction_limit(limit);
+    }
+
     /// Enable or disable debug mode
     pub fn set_debug(&mut self, enabled: bool) {
         self.inner.debug = enabled;
@@ -53,6 +130,57 @@ pub fn parse(source: &str) -> Result<JsValue, JsValue> {
         .into())
 }
 
+/// Parse AS Lang code and return the full syntax tree as JSON, with each
+/// statement's source span
+#[wasm_bindgen]
+pub fn parse_ast(source: &str) -> Result<JsValue, JsValue> {
+    let ast = aslang::parser::Parser::parse(source)
+        .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+    
+    Ok(serde_json::to_string(&ast)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?
+        .into())
+}
+
+/// Tokenize AS Lang code for syntax highlighting. Returns JSON
+/// `{ tokens, error }`: each token is `{ kind, value, line, column, length }`
+/// with `value` its source text. On a lexer error, `tokens` holds those read
+/// before it and `error` is `{ message, line, column }`; otherwise `error`
+/// is null.
+#[wasm_bindgen]
+pub fn tokenize(source: &str) -> JsValue {
+    JsValue::from(tokenize_json(source))
+}
+
+fn tokenize_json(source: &str) -> String {
+    let (tokens, error) = aslang::lexer::Lexer::new(source).tokenize_partial();
+    let chars: Vec<char> = source.chars().collect();
+    // Char offset at which each line starts, to turn spans into source text
+    let mut line_starts = vec![0];
+    line_starts.extend(chars.iter().enumerate().filter(|(_, &c)| c == '\n').map(|(i, _)| i + 1));
+    let offset = |location: &SourceLocation| {
+        let start = line_starts.get(location.line - 1).copied().unwrap_or(chars.len());
+        (start + location.column - 1).min(chars.len())
+    };
+    
+    let tokens = tokens.iter().map(|(token, span)| {
+        let (start, end) = (offset(&span.start), offset(&span.end));
+        TokenInfo {
+            kind: token.kind_name().to_string(),
+            value: chars[start..end].iter().collect(),
+            line: span.start.line,
+            column: span.start.column,
+            length: end - start,
+        }
+    }).collect();
+    let result = TokenizeResult {
+        tokens,
+        error: error.map(|e| TokenError { message: e.message, line: e.location.line, column: e.location.column }),
+    };
+    
+    serde_json::to_string(&result).unwrap_or_else(|e| e.to_string())
+}
+
 /// Compile AS Lang code and return compilation result as JSON
 #[wasm_bindgen]
 pub fn compile(source: &str) -> Result<JsValue, JsValue> {
@@ -60,11 +188,11 @@ pub fn compile(source: &str) -> Result<JsValue, JsValue> {
         .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
     
     let mut compiler = aslang::compiler::Compiler::new();
-    let bytecode = compiler.compile(&ast)
+    let program = compiler.compile(&ast)
         .map_err(|e| JsValue::from_str(&format!("Compile error: {}", e)))?;
     
     let result = CompilationResult {
-        bytecode_len: bytecode.len(),
+        bytecode_len: program.main.bytecode.len(),
         success: true,
         message: "Compiled successfully".to_string(),
     };
@@ -88,6 +216,28 @@ struct ParseResult {
     message: String,
 }
 
+#[derive(Serialize, Deserialize)]
+struct TokenInfo {
+    kind: String,
+    value: String,
+    line: usize,
+    column: usize,
+    length: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TokenError {
+    message: String,
+    line: usize,
+    column: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TokenizeResult {
+    tokens: Vec<TokenInfo>,
+    error: Option<TokenError>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct CompilationResult {
     bytecode_len: usize,
@@ -100,4 +250,86 @@ struct CompilationResult {
 pub fn init() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// A runtime whose output writer records each line it is handed.
+    fn recording_runtime() -> (aslang::runtime::Runtime, Rc<RefCell<Vec<String>>>) {
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&lines);
+        let mut runtime = aslang::runtime::Runtime::new();
+        runtime.set_output_writer(LineWriter::new(move |text: &str| {
+            sink.borrow_mut().push(text.to_string());
+            Ok(())
+        }));
+        (runtime, lines)
+    }
+
+    #[test]
+    fn test_line_writer_splits_writes_into_lines() {
+        let mut lines = Vec::new();
+        let mut writer = LineWriter::new(|text: &str| {
+            lines.push(text.to_string());
+            Ok(())
+        });
+        std::io::Write::write_all(&mut writer, b"one\ntw").unwrap();
+        std::io::Write::write_all(&mut writer, b"o\nthree\n\n").unwrap();
+        drop(writer);
+        assert_eq!(lines, vec!["one\n", "two\n", "three\n", "\n"]);
+    }
+
+    #[test]
+    fn test_loop_output_arrives_one_line_per_call() {
+        let (mut runtime, lines) = recording_runtime();
+        let output = runtime.execute("let i = 0; while i < 3 { output i, \"x\"; i = i + 1; }").unwrap();
+        assert_eq!(output, "0 x\n1 x\n2 x\n");
+        assert_eq!(*lines.borrow(), vec!["0 x\n", "1 x\n", "2 x\n"]);
+    }
+
+    #[test]
+    fn test_input_prompt_is_flushed_as_partial_line() {
+        let (mut runtime, lines) = recording_runtime();
+        runtime.set_input_handler(|_| Ok("Ada".to_string()));
+        runtime.execute("output \"hi\"; input \"Name? \" name; output name;").unwrap();
+        assert_eq!(*lines.borrow(), vec!["hi\n", "Name? ", "Ada\n"]);
+    }
+
+    #[test]
+    fn test_tokenize_reports_kinds_and_source_text() {
+        let result: serde_json::Value = serde_json::from_str(&tokenize_json("let name = \"hi\";\noutput 1_000;")).unwrap();
+        assert_eq!(result["error"], serde_json::Value::Null);
+        let tokens = result["tokens"].as_array().unwrap();
+        let summary: Vec<(&str, &str)> = tokens.iter()
+            .map(|token| (token["kind"].as_str().unwrap(), token["value"].as_str().unwrap()))
+            .collect();
+        assert_eq!(summary, vec![
+            ("Let", "let"), ("Identifier", "name"), ("Eq", "="), ("String", "\"hi\""), ("Semicolon", ";"),
+            ("Output", "output"), ("Number", "1_000"), ("Semicolon", ";"), ("EOF", ""),
+        ]);
+        assert_eq!(tokens[6], serde_json::json!({ "kind": "Number", "value": "1_000", "line": 2, "column": 8, "length": 5 }));
+    }
+
+    #[test]
+    fn test_tokenize_keeps_tokens_before_an_error() {
+        let result: serde_json::Value = serde_json::from_str(&tokenize_json("let x = 1;\nlet s = \"open")).unwrap();
+        let kinds: Vec<&str> = result["tokens"].as_array().unwrap().iter()
+            .map(|token| token["kind"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, vec!["Let", "Identifier", "Eq", "Number", "Semicolon", "Let", "Identifier", "Eq"]);
+        assert_eq!(result["error"]["line"], 2);
+        assert_eq!(result["error"]["column"], 9);
+        assert!(result["error"]["message"].as_str().unwrap().contains("Unterminated string"));
+    }
+
+    #[test]
+    fn test_handler_errors_stop_execution() {
+        let mut runtime = aslang::runtime::Runtime::new();
+        runtime.set_output_writer(LineWriter::new(|_: &str| Err(std::io::Error::other("handler threw"))));
+        let err = runtime.execute("output 1; output 2;").unwrap_err();
+        assert_eq!(err.message, "Failed to write output: handler threw");
+    }
+}
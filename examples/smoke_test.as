@@ -12,6 +12,6 @@ if x < y {
 let counter = 3;
 while counter > 0 {
     output counter;
-    let counter = counter - 1;
+    counter = counter - 1;
 }
 output "Done";
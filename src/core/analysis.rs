@@ -0,0 +1,249 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+//! Queries over a source document for editor tooling, such as the language
+//! server. Positions are 1-based, like `SourceLocation`.
+
+use crate::error::{ASError, SourceLocation, Span};
+use std::path::{Path, PathBuf};
+use crate::lexer::{Lexer, Token, KEYWORDS};
+use crate::parser::{Block, Parser, Statement};
+use crate::types::{builtin_signature, Declaration, Type, TypeChecker};
+
+/// The identifier token covering `position`, with its span.
+pub fn identifier_at(source: &str, position: &SourceLocation) -> Option<(String, Span)> {
+    let (tokens, _) = Lexer::new(source).tokenize_partial();
+    tokens.into_iter().find_map(|(token, span)| match token {
+        Token::Identifier(name) if covers(&span, position) => Some((name, span)),
+        _ => None,
+    })
+}
+
+/// The path a `file://` URI names, with `%XX` escapes decoded; `None` for
+/// other schemes and escapes that aren't valid UTF-8.
+pub fn file_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let escape = encoded.get(i + 1..i + 3)
+            .filter(|_| encoded[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escape {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            None => {
+                bytes.push(encoded[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+/// The errors to show for `source`: all of its parse errors, or else the
+/// first type error. `file` is where the document lives, for resolving
+/// imports.
+pub fn diagnostics(source: &str, file: Option<&Path>) -> Vec<ASError> {
+    let (ast, errors) = Parser::parse_recovering(source);
+    // Checking a partial tree would only add noise
+    if !errors.is_empty() {
+        return errors;
+    }
+    let mut checker = TypeChecker::new();
+    checker.current_file = file.map(Path::to_path_buf);
+    checker.check(&ast).err().into_iter().collect()
+}
+
+/// Every declaration in `source`, in order, with its checked type. A
+/// program that fails to type check yields those before the error; one that
+/// fails to parse yields none.
+pub fn declarations(source: &str) -> Vec<Declaration> {
+    let Ok(ast) = Parser::parse(source) else { return Vec::new() };
+    let mut checker = TypeChecker::new();
+    checker.record_declarations = true;
+    // Keep going past unknown names, which are common while editing
+    checker.allow_undefined = true;
+    let _ = checker.check(&ast);
+    checker.declarations
+}
+
+/// The type of the variable or function named at `position`, if known.
+pub fn type_at(source: &str, position: &SourceLocation) -> Option<Type> {
+    let (name, _) = identifier_at(source, position)?;
+    match declaration_of(&declarations(source), &name, position) {
+        Some(declaration) => Some(declaration.ty.clone()),
+        None => builtin_signature(&name),
+    }
+}
+
+/// The span of the statement declaring the variable or function named at
+/// `position`; `None` for built-ins and undefined names.
+pub fn definition_at(source: &str, position: &SourceLocation) -> Option<Span> {
+    let (name, _) = identifier_at(source, position)?;
+    declaration_of(&declarations(source), &name, position).map(|declaration| declaration.span.clone())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolKind {
+    Variable,
+    Function,
+}
+
+/// An entry in a document's outline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The declaring statement.
+    pub span: Span,
+    /// For a function, the functions and variables declared in its body.
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// The outline of `source`: its top-level functions and variables, with
+/// each function's own declarations nested under it. Empty if it doesn't
+/// parse.
+pub fn document_symbols(source: &str) -> Vec<DocumentSymbol> {
+    let Ok(ast) = Parser::parse(source) else { return Vec::new() };
+    ast.statements.iter().filter_map(|statement| symbol(&statement.node, &statement.span)).collect()
+}
+
+fn symbol(statement: &Statement, span: &Span) -> Option<DocumentSymbol> {
+    let (name, kind, children) = match statement {
+        Statement::Let { name, .. } => (name, SymbolKind::Variable, Vec::new()),
+        Statement::Function { name, body, .. } => {
+            let mut children = Vec::new();
+            local_symbols(body, &mut children);
+            (name, SymbolKind::Function, children)
+        }
+        _ => return None,
+    };
+    Some(DocumentSymbol { name: name.clone(), kind, span: span.clone(), children })
+}
+
+/// Adds the symbols declared anywhere in a function body, including inside
+/// its loops and conditionals.
+fn local_symbols(body: &Block, symbols: &mut Vec<DocumentSymbol>) {
+    for statement in body {
+        symbols.extend(symbol(&statement.node, &statement.span));
+        match &statement.node {
+            Statement::If { then_branch, elif_branches, else_branch, .. } => {
+                local_symbols(then_branch, symbols);
+                for (_, branch) in elif_branches {
+                    local_symbols(branch, symbols);
+                }
+                if let Some(branch) = else_branch {
+                    local_symbols(branch, symbols);
+                }
+            }
+            Statement::While { body, .. } | Statement::For { body, .. } => local_symbols(body, symbols),
+            _ => {}
+        }
+    }
+}
+
+/// What a completion item inserts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompletionKind {
+    Keyword,
+    Variable,
+    Function,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+}
+
+impl Completion {
+    pub fn new(label: &str, kind: CompletionKind) -> Self {
+        Completion { label: label.to_string(), kind }
+    }
+}
+
+/// Keywords, then the names declared before `position` in scopes enclosing
+/// it. If the document doesn't parse, the lines above `position` are tried
+/// on their own, since the line being typed is usually the broken one.
+pub fn completions(source: &str, position: &SourceLocation) -> Vec<Completion> {
+    let mut items: Vec<Completion> = KEYWORDS.iter()
+        .map(|keyword| Completion::new(keyword, CompletionKind::Keyword))
+        .collect();
+    let above: String = source.lines().take(position.line.saturating_sub(1)).collect::<Vec<_>>().join("\n");
+    if let Ok(ast) = Parser::parse(source).or_else(|_| Parser::parse(&above)) {
+        collect_names(&ast.statements, position, &mut items);
+    }
+    
+    let mut seen = std::collections::HashSet::new();
+    items.retain(|item| seen.insert(item.label.clone()));
+    items
+}
+
+/// Adds the names `block` declares before `position`, descending into the
+/// statement that contains it.
+fn collect_names(block: &Block, position: &SourceLocation, items: &mut Vec<Completion>) {
+    for statement in block {
+        if is_after(&statement.span.start, position) {
+            break;
+        }
+        // A function's own name is visible in its body, a variable's only after it
+        let inside = is_after(&statement.span.end, position);
+        match &statement.node {
+            Statement::Let { name, .. } | Statement::Input { target: name, .. } if !inside => {
+                items.push(Completion::new(name, CompletionKind::Variable));
+            }
+            Statement::Import { names: Some(names), .. } if !inside => {
+                for name in names {
+                    items.push(Completion::new(name, CompletionKind::Variable));
+                }
+            }
+            Statement::Function { name, params, body, .. } => {
+                items.push(Completion::new(name, CompletionKind::Function));
+                if inside {
+                    for param in params {
+                        items.push(Completion::new(param, CompletionKind::Variable));
+                    }
+                    collect_names(body, position, items);
+                }
+            }
+            Statement::If { then_branch, elif_branches, else_branch, .. } if inside => {
+                collect_names(then_branch, position, items);
+                for (_, body) in elif_branches {
+                    collect_names(body, position, items);
+                }
+                if let Some(body) = else_branch {
+                    collect_names(body, position, items);
+                }
+            }
+            Statement::While { body, .. } if inside => collect_names(body, position, items),
+            Statement::For { init, body, .. } if inside => {
+                if let Some(Statement::Let { name, .. }) = init.as_deref() {
+                    items.push(Completion::new(name, CompletionKind::Variable));
+                }
+                collect_names(body, position, items);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The declaration `name` at `position` refers to: the last one made at or
+/// before it, or else the first one after it, as for a function called
+/// above its definition.
+fn declaration_of<'a>(declarations: &'a [Declaration], name: &str, position: &SourceLocation) -> Option<&'a Declaration> {
+    let mut matching = declarations.iter().filter(|declaration| declaration.name == name);
+    let first = matching.clone().next();
+    matching.rfind(|declaration| !is_after(&declaration.span.start, position)).or(first)
+}
+
+fn is_after(a: &SourceLocation, b: &SourceLocation) -> bool {
+    (a.line, a.column) > (b.line, b.column)
+}
+
+fn covers(span: &Span, position: &SourceLocation) -> bool {
+    span.start.line == position.line
+        && span.start.column <= position.column
+        && position.column < span.end.column
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+//! Compile-time evaluation of operators over literal operands.
+
+use super::Constant;
+use crate::parser::{BinaryOp, Expression, UnaryOp};
+use crate::runtime::MAX_SAFE_INTEGER;
+use std::rc::Rc;
+
+/// The value `expr` evaluates to, when it is made only of literals and the
+/// result is exactly what the VM would compute. Anything the VM could
+/// reject at run time (division by zero, out-of-range integers under
+/// `strict_integers`) or compare differently (floats under `float_epsilon`)
+/// is left unfolded.
+pub(super) fn fold(expr: &Expression) -> Option<Constant> {
+    match expr {
+        Expression::Number(n) => Some(number(*n)),
+        Expression::String(s) => Some(Constant::String(Rc::from(s.as_str()))),
+        Expression::Boolean(b) => Some(Constant::Boolean(*b)),
+        Expression::Grouping(inner) => fold(inner),
+        Expression::UnaryOp { operator, operand } => unary(operator, fold(operand)?),
+        Expression::BinaryOp { left, operator, right } => binary(operator, fold(left)?, fold(right)?),
+        _ => None,
+    }
+}
+
+/// A numeric literal, typed the way the compiler emits it.
+fn number(n: f64) -> Constant {
+    if n.fract() == 0.0 && n.abs() <= MAX_SAFE_INTEGER {
+        Constant::Integer(n as i64)
+    } else {
+        Constant::Number(n)
+    }
+}
+
+fn as_float(constant: &Constant) -> Option<f64> {
+    match constant {
+        Constant::Integer(n) => Some(*n as f64),
+        Constant::Number(n) => Some(*n),
+        _ => None,
+    }
+}
+
+/// Keeps a result only if no runtime setting could treat it differently.
+fn safe(constant: Constant) -> Option<Constant> {
+    match constant {
+        Constant::Integer(n) if n.unsigned_abs() > MAX_SAFE_INTEGER as u64 => None,
+        Constant::Number(n) if !n.is_finite() || n.abs() > MAX_SAFE_INTEGER => None,
+        other => Some(other),
+    }
+}
+
+fn unary(operator: &UnaryOp, operand: Constant) -> Option<Constant> {
+    match (operator, operand) {
+        (UnaryOp::Negate, Constant::Integer(n)) => safe(Constant::Integer(n.checked_neg()?)),
+        (UnaryOp::Negate, Constant::Number(n)) => safe(Constant::Number(-n)),
+        (UnaryOp::Not, Constant::Boolean(b)) => Some(Constant::Boolean(!b)),
+        _ => None,
+    }
+}
+
+fn binary(operator: &BinaryOp, left: Constant, right: Constant) -> Option<Constant> {
+    if let (Constant::String(a), Constant::String(b), BinaryOp::Add) = (&left, &right, operator) {
+        return Some(Constant::String(Rc::from(format!("{}{}", a, b).as_str())));
+    }
+    if let (Constant::Boolean(a), Constant::Boolean(b)) = (&left, &right) {
+        return match operator {
+            BinaryOp::And => Some(Constant::Boolean(*a && *b)),
+            BinaryOp::Or => Some(Constant::Boolean(*a || *b)),
+            BinaryOp::Eq => Some(Constant::Boolean(a == b)),
+            BinaryOp::Ne => Some(Constant::Boolean(a != b)),
+            _ => None,
+        };
+    }
+    let (a, b) = (as_float(&left)?, as_float(&right)?);
+    let integers = match (&left, &right) {
+        (Constant::Integer(x), Constant::Integer(y)) => Some((*x, *y)),
+        _ => None,
+    };
+    // Two integers stay integral unless the operation overflows, as in the VM
+    let arithmetic = |integer_op: fn(i64, i64) -> Option<i64>, float_op: fn(f64, f64) -> f64| {
+        match integers.and_then(|(x, y)| integer_op(x, y)) {
+            Some(n) => safe(Constant::Integer(n)),
+            None => safe(Constant::Number(float_op(a, b))),
+        }
+    };
+    match operator {
+        BinaryOp::Add => arithmetic(i64::checked_add, |x, y| x + y),
+        BinaryOp::Subtract => arithmetic(i64::checked_sub, |x, y| x - y),
+        BinaryOp::Multiply => arithmetic(i64::checked_mul, |x, y| x * y),
+        BinaryOp::Divide if b != 0.0 => safe(Constant::Number(a / b)),
+        BinaryOp::Modulo if b != 0.0 => arithmetic(i64::checked_rem, |x, y| x % y),
+        BinaryOp::Power => arithmetic(|x, y| x.checked_pow(u32::try_from(y).ok()?), f64::powf),
+        BinaryOp::Lt => Some(Constant::Boolean(a < b)),
+        BinaryOp::Le => Some(Constant::Boolean(a <= b)),
+        BinaryOp::Gt => Some(Constant::Boolean(a > b)),
+        BinaryOp::Ge => Some(Constant::Boolean(a >= b)),
+        // Float equality depends on the runtime's `float_epsilon`
+        BinaryOp::Eq => integers.map(|(x, y)| Constant::Boolean(x == y)),
+        BinaryOp::Ne => integers.map(|(x, y)| Constant::Boolean(x != y)),
+        _ => None,
+    }
+}
@@ -1,24 +1,53 @@
 // Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
 
-use crate::parser::{AST, Expression, Statement, BinaryOp, UnaryOp};
-use crate::error::{ASError, ErrorKind, SourceLocation};
-use std::collections::HashMap;
+use crate::parser::{AST, Block, Expression, Statement, BinaryOp, UnaryOp};
+use crate::error::{ASError, ErrorKind, SourceLocation, Warning};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
-#[derive(Debug, Clone)]
+mod fold;
+pub mod serialize;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Opcode {
-    LoadConst(f64),
-    LoadString(String),
-    LoadBool(bool),
+    /// Push the value at the given index of the code object's constant pool.
+    LoadConst(usize),
+    /// Push the global variable with the given name.
     LoadVar(String),
+    /// Pop a value into the global variable with the given name.
     StoreVar(String),
+    /// Push the value in the given local slot of the running code object.
+    LoadLocal(usize),
+    /// Pop a value into the given local slot of the running code object.
+    StoreLocal(usize),
+    /// Call a built-in (or not yet known) function by name with N arguments.
     Call(String, usize),
+    /// Call the compiled function at the given index with N arguments.
+    CallFunction(usize, usize),
+    /// Push the compiled function at the given index as a value.
+    LoadFunction(usize),
     MakeArray(usize),
+    /// Pop N key/value pairs (key below value) and push them as a map.
+    MakeMap(usize),
+    /// Pop an index and a container; push the element at that index.
     GetIndex,
+    /// Pop a value, an index and a container; push the container with that
+    /// element replaced.
     SetIndex,
     Return,
-    Output,
-    Input,
+    /// Print the top of the stack; `true` ends the line, `false` separates
+    /// the value from the next one with a space.
+    Output(bool),
+    /// `Output` of a string literal, written straight from the constant pool
+    /// without creating a value.
+    OutputStr(usize, bool),
+    /// Read a line of input and push it; `true` first pops a prompt and
+    /// writes it without ending the line.
+    Input(bool),
     Import(String),
+    /// Run a module with its own globals, if not run yet, and bind the
+    /// named ones (or its functions) as globals of the importer.
+    ImportNames(String, Vec<String>),
     
     // Arithmetic
     Add, Subtract, Multiply, Divide, Modulo, Power,
@@ -31,91 +60,308 @@ pub enum Opcode {
     
     // Unary
     Negate,
+    /// Invert the bits of an integer-valued number.
+    BitwiseNot,
     
     // Control Flow
     Jump(usize),
     JumpIfFalse(usize),
+    JumpIfTrue(usize),
+    /// Jumps relative to the jump's own position, so they stay valid when
+    /// the bytecode is moved.
+    JumpRel(isize),
+    JumpIfFalseRel(isize),
+    JumpIfTrueRel(isize),
     
     // Stack manipulation
     Pop,
 }
 
+/// A unit of compiled code: the main program or a single function body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeObject {
+    pub name: String,
+    pub arity: usize,
+    pub params: Vec<String>,
+    pub bytecode: Vec<Opcode>,
+    /// Source location of each opcode, indexed in parallel with `bytecode`.
+    pub lines: Vec<SourceLocation>,
+    /// Deduplicated literals, shared by every load that uses them.
+    pub constants: Vec<Constant>,
+}
+
+/// A literal stored in a code object's constant pool.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Integer(i64),
+    Number(f64),
+    String(Rc<str>),
+    Char(char),
+    Boolean(bool),
+}
+
+/// Deduplication key for the constant pool; numbers compare by bit pattern
+/// so that, for example, `0` and `-0` stay distinct.
+#[derive(PartialEq, Eq, Hash)]
+enum ConstantKey {
+    Integer(i64),
+    Number(u64),
+    String(Rc<str>),
+    Char(char),
+    Boolean(bool),
+}
+
+impl From<&Constant> for ConstantKey {
+    fn from(constant: &Constant) -> Self {
+        match constant {
+            Constant::Integer(n) => ConstantKey::Integer(*n),
+            Constant::Number(n) => ConstantKey::Number(n.to_bits()),
+            Constant::String(s) => ConstantKey::String(Rc::clone(s)),
+            Constant::Char(c) => ConstantKey::Char(*c),
+            Constant::Boolean(b) => ConstantKey::Boolean(*b),
+        }
+    }
+}
+
+impl CodeObject {
+    pub fn new(name: &str, params: Vec<String>) -> Self {
+        CodeObject {
+            name: name.to_string(),
+            arity: params.len(),
+            params,
+            bytecode: Vec::new(),
+            lines: Vec::new(),
+            constants: Vec::new(),
+        }
+    }
+}
+
+/// A compiled program: its top-level code plus the function table
+/// that `Opcode::CallFunction` indexes into.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub main: CodeObject,
+    pub functions: Vec<CodeObject>,
+}
+
 pub struct Compiler {
     pub bytecode: Vec<Opcode>,
-    variables: HashMap<String, usize>,
-    functions: HashMap<String, usize>,
+    /// Source location of each opcode, indexed in parallel with `bytecode`.
+    pub lines: Vec<SourceLocation>,
+    /// Constant pool of the code object being compiled.
+    pub constants: Vec<Constant>,
+    constant_indices: HashMap<ConstantKey, usize>,
+    pub functions: Vec<CodeObject>,
+    /// Diagnostics from the last `compile`, such as same-scope redeclarations.
+    pub warnings: Vec<Warning>,
+    /// Names of the top-level variables of the main program, which live in
+    /// the runtime's global map rather than in slots.
+    globals: HashSet<String>,
+    /// Variables in slots of the code object being compiled, indexed by
+    /// slot; a block's locals are released when it ends.
+    locals: Vec<Local>,
+    /// Whether a function body is being compiled.
+    in_function: bool,
+    function_indices: HashMap<String, usize>,
+    /// Emit position-independent `JumpRel`/`JumpIfFalseRel`/`JumpIfTrueRel` instead of
+    /// absolute jumps.
+    pub relative_jumps: bool,
+    /// Evaluate operators over literal operands at compile time, so
+    /// `2 + 3 * 4` compiles to a single `LoadConst`.
+    pub optimize: bool,
+    /// Names declared by `let`/`fn` in each open block, innermost last.
+    scopes: Vec<HashMap<String, SourceLocation>>,
+    /// Enclosing loops of the code being compiled, innermost last.
+    loops: Vec<LoopContext>,
+    location: SourceLocation,
+}
+
+/// A variable stored in a local slot.
+struct Local {
+    name: String,
+    /// Number of open scopes where it was declared.
+    depth: usize,
+}
+
+/// Jumps still to be resolved for a loop being compiled.
+#[derive(Default)]
+struct LoopContext {
+    /// Where `continue` jumps back to, when already known.
+    continue_target: Option<usize>,
+    /// Forward `continue` jumps, patched once the target is emitted.
+    continue_jumps: Vec<usize>,
+    break_jumps: Vec<usize>,
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Compiler {
     pub fn new() -> Self {
         Compiler {
             bytecode: Vec::new(),
-            variables: HashMap::new(),
-            functions: HashMap::new(),
+            lines: Vec::new(),
+            constants: Vec::new(),
+            constant_indices: HashMap::new(),
+            functions: Vec::new(),
+            warnings: Vec::new(),
+            globals: HashSet::new(),
+            locals: Vec::new(),
+            in_function: false,
+            function_indices: HashMap::new(),
+            relative_jumps: false,
+            optimize: false,
+            scopes: Vec::new(),
+            loops: Vec::new(),
+            location: SourceLocation::new(0, 0),
         }
     }
 
-    pub fn compile(&mut self, ast: &AST) -> Result<Vec<Opcode>, ASError> {
+    pub fn compile(&mut self, ast: &AST) -> Result<Program, ASError> {
         self.bytecode.clear();
+        self.lines.clear();
+        self.constants.clear();
+        self.constant_indices.clear();
+        self.warnings.clear();
+        self.scopes.clear();
+        self.locals.clear();
+        self.in_function = false;
+        self.loops.clear();
         
-        for statement in &ast.statements {
-            self.compile_statement(statement)?;
+        // Declare top-level functions up front so calls may precede definitions
+        for stmt in &ast.statements {
+            if let Statement::Function { name, params, .. } = &stmt.node {
+                self.declare_function(name, params);
+            }
         }
         
-        Ok(self.bytecode.clone())
+        self.compile_block(&ast.statements)?;
+        
+        let mut main = CodeObject::new("<main>", Vec::new());
+        main.bytecode = self.bytecode.clone();
+        main.lines = self.lines.clone();
+        main.constants = self.constants.clone();
+        Ok(Program { main, functions: self.functions.clone() })
+    }
+    
+    /// Compiles a module imported by name. Its functions join the shared
+    /// table under fresh indices, so names it shares with the importer's
+    /// functions neither resolve to nor replace them.
+    pub fn compile_isolated(&mut self, ast: &AST) -> Result<Program, ASError> {
+        let importer_functions = std::mem::take(&mut self.function_indices);
+        let importer_globals = std::mem::take(&mut self.globals);
+        let result = self.compile(ast);
+        self.function_indices = importer_functions;
+        self.globals = importer_globals;
+        result
+    }
+    
+    fn declare_function(&mut self, name: &str, params: &[String]) -> usize {
+        let code = CodeObject::new(name, params.to_vec());
+        match self.function_indices.get(name) {
+            Some(&index) => {
+                self.functions[index] = code;
+                index
+            }
+            None => {
+                self.functions.push(code);
+                self.function_indices.insert(name.to_string(), self.functions.len() - 1);
+                self.functions.len() - 1
+            }
+        }
     }
 
     fn compile_statement(&mut self, statement: &Statement) -> Result<(), ASError> {
         match statement {
             Statement::Let { name, value, type_annotation: _ } => {
+                self.declare_name(name, "Variable");
                 self.compile_expression(value)?;
-                self.bytecode.push(Opcode::StoreVar(name.clone()));
-                self.variables.insert(name.clone(), self.variables.len());
+                self.define_variable(name);
             }
-            Statement::Output(expr) => {
-                self.compile_expression(expr)?;
-                self.bytecode.push(Opcode::Output);
+            Statement::Output(exprs) => {
+                for (i, expr) in exprs.iter().enumerate() {
+                    let newline = i == exprs.len() - 1;
+                    if let Expression::String(s) = expr {
+                        let index = self.add_constant(Constant::String(Rc::from(s.as_str())));
+                        self.emit(Opcode::OutputStr(index, newline));
+                    } else {
+                        self.compile_expression(expr)?;
+                        self.emit(Opcode::Output(newline));
+                    }
+                }
             }
             Statement::Input { prompt, target } => {
                 if let Some(p) = prompt {
                     self.compile_expression(p)?;
-                    self.bytecode.push(Opcode::Output); // Print prompt
                 }
-                self.bytecode.push(Opcode::Input);
-                self.bytecode.push(Opcode::StoreVar(target.clone()));
-                self.variables.insert(target.clone(), self.variables.len());
+                self.emit(Opcode::Input(prompt.is_some()));
+                // Input into an unknown name declares it
+                if self.resolve_local(target).is_some() || self.globals.contains(target) {
+                    self.emit_store(target);
+                } else {
+                    self.define_variable(target);
+                }
             }
-            Statement::Import { path } => {
-                self.bytecode.push(Opcode::Import(path.clone()));
+            Statement::Import { path, names: None } => {
+                self.emit(Opcode::Import(path.clone()));
+            }
+            Statement::Import { path, names: Some(names) } => {
+                // The runtime binds the names as globals, which only the top level sees
+                if self.in_function || self.scopes.len() > 1 {
+                    return Err(self.error("Named imports are only allowed at the top level"));
+                }
+                self.globals.extend(names.iter().cloned());
+                self.emit(Opcode::ImportNames(path.clone(), names.clone()));
+            }
+            Statement::ExpressionStmt(Expression::IndexAssign { target, index, value }) => {
+                // SetIndex yields the updated container, which replaces the old one
+                self.emit_load(target);
+                self.compile_expression(index)?;
+                self.compile_expression(value)?;
+                self.emit(Opcode::SetIndex);
+                self.emit_store(target);
             }
             Statement::ExpressionStmt(expr) => {
                 self.compile_expression(expr)?;
-                self.bytecode.push(Opcode::Pop);
+                self.emit(Opcode::Pop);
             }
-            Statement::Function { name, params, body, return_type: _ } => {
-                // ToDo: Function compilation needs jump over body or separate code segments
-                // For simplicity now, we'll put it in main stream but need a jump over it
-                // A better approach is to compile functions separately
-                
-                let jump_over = self.emit_jump(Opcode::Jump(0));
+            Statement::Function { name, params, body, .. } => {
+                // Functions compile into their own code object, leaving nothing inline
+                self.declare_name(name, "Function");
+                let index = self.declare_function(name, params);
+                let outer_bytecode = std::mem::take(&mut self.bytecode);
+                let outer_lines = std::mem::take(&mut self.lines);
+                let outer_constants = std::mem::take(&mut self.constants);
+                let outer_constant_indices = std::mem::take(&mut self.constant_indices);
+                // `break` inside the body must not reach a loop around the definition
+                let outer_loops = std::mem::take(&mut self.loops);
+                // Parameters take the first slots; enclosing locals are not visible
+                let depth = self.scopes.len() + 1;
+                let params = params.iter().map(|param| Local { name: param.clone(), depth }).collect();
+                let outer_locals = std::mem::replace(&mut self.locals, params);
+                let outer_in_function = std::mem::replace(&mut self.in_function, true);
                 
-                let start_pos = self.bytecode.len();
-                self.functions.insert(name.clone(), start_pos);
-                
-                // Add parameters to variables scope (simplified)
-                for param in params {
-                    self.variables.insert(param.clone(), self.variables.len());
-                }
-                
-                for stmt in body {
-                    self.compile_statement(stmt)?;
-                }
+                let result = self.compile_block(body);
                 
                 // Ensure implicit return
-                self.bytecode.push(Opcode::LoadConst(0.0)); // Default return
-                self.bytecode.push(Opcode::Return);
+                self.emit_constant(Constant::Number(0.0)); // Default return
+                self.emit(Opcode::Return);
+                
+                let bytecode = std::mem::replace(&mut self.bytecode, outer_bytecode);
+                let lines = std::mem::replace(&mut self.lines, outer_lines);
+                let constants = std::mem::replace(&mut self.constants, outer_constants);
+                self.constant_indices = outer_constant_indices;
+                self.loops = outer_loops;
+                self.locals = outer_locals;
+                self.in_function = outer_in_function;
+                result?;
                 
-                self.patch_jump(jump_over);
+                self.functions[index].bytecode = bytecode;
+                self.functions[index].lines = lines;
+                self.functions[index].constants = constants;
             }
             Statement::If { condition, then_branch, elif_branches, else_branch } => {
                 let mut exit_jumps = Vec::new();
@@ -127,7 +373,7 @@ impl Compiler {
                 self.compile_block(then_branch)?;
                 exit_jumps.push(self.emit_jump(Opcode::Jump(0)));
                 
-                self.patch_jump(jump_to_next);
+                self.patch_jump(jump_to_next)?;
                 
                 // 2. Compile 'elif's
                 for (elif_cond, elif_body) in elif_branches {
@@ -137,7 +383,7 @@ impl Compiler {
                     self.compile_block(elif_body)?;
                     exit_jumps.push(self.emit_jump(Opcode::Jump(0)));
                     
-                    self.patch_jump(jump_to_next);
+                    self.patch_jump(jump_to_next)?;
                 }
                 
                 // 3. Compile 'else'
@@ -146,13 +392,8 @@ impl Compiler {
                 }
                 
                 // 4. Patch all jumps to end
-                let end_pos = self.bytecode.len();
                 for jump in exit_jumps {
-                    // Manually patch to end_pos
-                     match &mut self.bytecode[jump] {
-                        Opcode::Jump(ref mut val) => *val = end_pos,
-                        _ => panic!("Expected Jump opcode"),
-                    }
+                    self.patch_jump(jump)?;
                 }
             }
             Statement::While { condition, body } => {
@@ -161,12 +402,16 @@ impl Compiler {
                 
                 let jump_out = self.emit_jump(Opcode::JumpIfFalse(0));
                 
+                self.loops.push(LoopContext { continue_target: Some(loop_start), ..Default::default() });
                 self.compile_block(body)?;
                 self.emit_loop(loop_start);
                 
-                self.patch_jump(jump_out);
+                self.patch_jump(jump_out)?;
+                self.patch_breaks()?;
             }
             Statement::For { init, condition, update, body } => {
+                // The loop variable is scoped to the loop, so sibling loops may reuse it
+                self.scopes.push(HashMap::new());
                 if let Some(init_stmt) = init {
                     self.compile_statement(init_stmt)?;
                 }
@@ -179,8 +424,14 @@ impl Compiler {
                     jump_out = Some(self.emit_jump(Opcode::JumpIfFalse(0)));
                 }
                 
+                // `continue` lands on the update, which is only emitted after the body
+                self.loops.push(LoopContext::default());
                 self.compile_block(body)?;
                 
+                let continues = std::mem::take(&mut self.loops.last_mut().unwrap().continue_jumps);
+                for jump in continues {
+                    self.patch_jump(jump)?;
+                }
                 if let Some(upd) = update {
                     self.compile_statement(upd)?;
                 }
@@ -188,56 +439,185 @@ impl Compiler {
                 self.emit_loop(loop_start);
                 
                 if let Some(jump) = jump_out {
-                    self.patch_jump(jump);
+                    self.patch_jump(jump)?;
                 }
+                self.patch_breaks()?;
+                self.end_scope();
             }
             Statement::Return(expr) => {
                 if let Some(e) = expr {
                     self.compile_expression(e)?;
                 } else {
-                    self.bytecode.push(Opcode::LoadConst(0.0)); // Null/Void
+                    self.emit_constant(Constant::Number(0.0)); // Null/Void
+                }
+                self.emit(Opcode::Return);
+            }
+            Statement::Break => {
+                if self.loops.is_empty() {
+                    return Err(self.error("'break' outside of a loop"));
+                }
+                let jump = self.emit_jump(Opcode::Jump(0));
+                self.loops.last_mut().unwrap().break_jumps.push(jump);
+            }
+            Statement::Continue => {
+                let Some(context) = self.loops.last() else {
+                    return Err(self.error("'continue' outside of a loop"));
+                };
+                match context.continue_target {
+                    Some(target) => self.emit_loop(target),
+                    None => {
+                        let jump = self.emit_jump(Opcode::Jump(0));
+                        self.loops.last_mut().unwrap().continue_jumps.push(jump);
+                    }
                 }
-                self.bytecode.push(Opcode::Return);
             }
-            _ => return Err(self.error("Statement not yet implemented in compiler")),
         }
         Ok(())
     }
     
-    fn compile_block(&mut self, statements: &Vec<Statement>) -> Result<(), ASError> {
-        for stmt in statements {
-            self.compile_statement(stmt)?;
+    fn compile_block(&mut self, statements: &Block) -> Result<(), ASError> {
+        self.scopes.push(HashMap::new());
+        let result = statements.iter().try_for_each(|stmt| {
+            let outer = std::mem::replace(&mut self.location, stmt.span.start.clone());
+            self.compile_statement(&stmt.node)?;
+            self.location = outer;
+            Ok(())
+        });
+        self.end_scope();
+        result
+    }
+    
+    /// Closes the innermost scope, releasing the slots of its locals.
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+        let depth = self.scopes.len();
+        while self.locals.last().is_some_and(|local| local.depth > depth) {
+            self.locals.pop();
         }
-        Ok(())
+    }
+    
+    /// Slot of the innermost visible local called `name`.
+    fn resolve_local(&self, name: &str) -> Option<usize> {
+        self.locals.iter().rposition(|local| local.name == name)
+    }
+    
+    /// Pops the top of the stack into a new variable: a global at the top
+    /// level of the main program, otherwise a slot of the current scope.
+    fn define_variable(&mut self, name: &str) {
+        if !self.in_function && self.scopes.len() <= 1 {
+            self.globals.insert(name.to_string());
+            self.emit(Opcode::StoreVar(name.to_string()));
+            return;
+        }
+        let depth = self.scopes.len();
+        // Redeclaring in the same scope reuses the slot
+        let slot = match self.resolve_local(name) {
+            Some(slot) if self.locals[slot].depth == depth => slot,
+            _ => {
+                self.locals.push(Local { name: name.to_string(), depth });
+                self.locals.len() - 1
+            }
+        };
+        self.emit(Opcode::StoreLocal(slot));
+    }
+    
+    fn emit_load(&mut self, name: &str) {
+        match self.resolve_local(name) {
+            Some(slot) => self.emit(Opcode::LoadLocal(slot)),
+            None => self.emit(Opcode::LoadVar(name.to_string())),
+        }
+    }
+    
+    fn emit_store(&mut self, name: &str) {
+        match self.resolve_local(name) {
+            Some(slot) => self.emit(Opcode::StoreLocal(slot)),
+            None => self.emit(Opcode::StoreVar(name.to_string())),
+        }
+    }
+    
+    /// Records a `let`/`fn` name in the innermost scope, warning when that
+    /// scope already defines it. Shadowing an outer scope is allowed.
+    fn declare_name(&mut self, name: &str, kind: &str) {
+        let location = self.location.clone();
+        let Some(scope) = self.scopes.last_mut() else { return };
+        if let Some(previous) = scope.insert(name.to_string(), location.clone()) {
+            self.warnings.push(Warning {
+                message: format!("{} '{}' redeclares a name already defined in this scope", kind, name),
+                location,
+                previous: Some(previous),
+            });
+        }
+    }
+    
+    fn emit(&mut self, opcode: Opcode) {
+        self.bytecode.push(opcode);
+        self.lines.push(self.location.clone());
     }
 
     fn compile_expression(&mut self, expr: &Expression) -> Result<(), ASError> {
+        if self.optimize && matches!(expr, Expression::BinaryOp { .. } | Expression::UnaryOp { .. } | Expression::Grouping(_)) {
+            if let Some(constant) = fold::fold(expr) {
+                self.emit_constant(constant);
+                return Ok(());
+            }
+        }
         match expr {
-            Expression::Number(n) => self.bytecode.push(Opcode::LoadConst(*n)),
-            Expression::String(s) => self.bytecode.push(Opcode::LoadString(s.clone())),
-            Expression::Boolean(b) => self.bytecode.push(Opcode::LoadBool(*b)),
+            // Whole literals in the exactly-representable range are integers
+            Expression::Number(n) if n.fract() == 0.0 && n.abs() <= crate::runtime::MAX_SAFE_INTEGER => {
+                self.emit_constant(Constant::Integer(*n as i64))
+            }
+            Expression::Number(n) => self.emit_constant(Constant::Number(*n)),
+            Expression::String(s) => self.emit_constant(Constant::String(Rc::from(s.as_str()))),
+            Expression::Char(c) => self.emit_constant(Constant::Char(*c)),
+            Expression::Boolean(b) => self.emit_constant(Constant::Boolean(*b)),
             Expression::Identifier(name) => {
-                // In real compiler we check if it exists or generic load
-                self.bytecode.push(Opcode::LoadVar(name.clone()));
+                // A function name not shadowed by a variable refers to the function itself
+                match self.function_indices.get(name) {
+                    Some(&index) if self.resolve_local(name).is_none() && !self.globals.contains(name) => {
+                        self.emit(Opcode::LoadFunction(index))
+                    }
+                    _ => self.emit_load(name),
+                }
+            },
+            Expression::BinaryOp { left, operator: operator @ (BinaryOp::And | BinaryOp::Or), right } => {
+                // Short-circuit: the right operand only runs when the left one
+                // doesn't already decide the result
+                self.compile_expression(left)?;
+                let (short_circuit, decided) = match operator {
+                    BinaryOp::And => (self.emit_jump(Opcode::JumpIfFalse(0)), false),
+                    _ => (self.emit_jump(Opcode::JumpIfTrue(0)), true),
+                };
+                self.compile_expression(right)?;
+                let end = self.emit_jump(Opcode::Jump(0));
+                self.patch_jump(short_circuit)?;
+                self.emit_constant(Constant::Boolean(decided));
+                self.patch_jump(end)?;
             },
             Expression::BinaryOp { left, operator, right } => {
                 self.compile_expression(left)?;
                 self.compile_expression(right)?;
                 match operator {
-                    BinaryOp::Add => self.bytecode.push(Opcode::Add),
-                    BinaryOp::Subtract => self.bytecode.push(Opcode::Subtract),
-                    BinaryOp::Multiply => self.bytecode.push(Opcode::Multiply),
-                    BinaryOp::Divide => self.bytecode.push(Opcode::Divide),
-                    BinaryOp::Eq => self.bytecode.push(Opcode::Eq),
-                    BinaryOp::Lt => self.bytecode.push(Opcode::Lt),
-                    BinaryOp::Gt => self.bytecode.push(Opcode::Gt),
+                    BinaryOp::Add => self.emit(Opcode::Add),
+                    BinaryOp::Subtract => self.emit(Opcode::Subtract),
+                    BinaryOp::Multiply => self.emit(Opcode::Multiply),
+                    BinaryOp::Divide => self.emit(Opcode::Divide),
+                    BinaryOp::Modulo => self.emit(Opcode::Modulo),
+                    BinaryOp::Power => self.emit(Opcode::Power),
+                    BinaryOp::Eq => self.emit(Opcode::Eq),
+                    BinaryOp::Ne => self.emit(Opcode::Ne),
+                    BinaryOp::Lt => self.emit(Opcode::Lt),
+                    BinaryOp::Le => self.emit(Opcode::Le),
+                    BinaryOp::Gt => self.emit(Opcode::Gt),
+                    BinaryOp::Ge => self.emit(Opcode::Ge),
                     _ => return Err(self.error("Binary operator not implemented")),
                 }
             },
             Expression::UnaryOp { operator, operand } => {
                 self.compile_expression(operand)?;
                 match operator {
-                    UnaryOp::Negate => self.bytecode.push(Opcode::Negate),
+                    UnaryOp::Negate => self.emit(Opcode::Negate),
+                    UnaryOp::Not => self.emit(Opcode::Not),
+                    UnaryOp::BitwiseNot => self.emit(Opcode::BitwiseNot),
                     _ => return Err(self.error("Unary operator not implemented")),
                 }
             },
@@ -248,7 +628,10 @@ impl Compiler {
                 
                 match &**function {
                     Expression::Identifier(name) => {
-                        self.bytecode.push(Opcode::Call(name.clone(), arguments.len()));
+                        match self.function_indices.get(name) {
+                            Some(&index) => self.emit(Opcode::CallFunction(index, arguments.len())),
+                            None => self.emit(Opcode::Call(name.clone(), arguments.len())),
+                        }
                     },
                     _ => return Err(self.error("Only named functions supported currently")),
                 }
@@ -257,53 +640,138 @@ impl Compiler {
                 for element in elements {
                     self.compile_expression(element)?;
                 }
-                self.bytecode.push(Opcode::MakeArray(elements.len()));
+                self.emit(Opcode::MakeArray(elements.len()));
+            },
+            Expression::Map { entries } => {
+                for (key, value) in entries {
+                    self.emit_constant(Constant::String(Rc::from(key.as_str())));
+                    self.compile_expression(value)?;
+                }
+                self.emit(Opcode::MakeMap(entries.len()));
+            },
+            Expression::Index { array, index } => {
+                self.compile_expression(array)?;
+                self.compile_expression(index)?;
+                self.emit(Opcode::GetIndex);
+            },
+            Expression::Grouping(inner) => self.compile_expression(inner)?,
+            Expression::IndexAssign { .. } => {
+                return Err(self.error("Index assignment cannot be used as a value"));
+            },
+            Expression::Assign { target, value } => {
+                self.compile_expression(value)?;
+                self.emit_store(target);
+                self.emit_load(target);
             },
-            _ => return Err(self.error("Expression not implemented")),
         }
         Ok(())
     }
     
+    /// Index of `constant` in the constant pool, adding it on first use.
+    fn add_constant(&mut self, constant: Constant) -> usize {
+        let key = ConstantKey::from(&constant);
+        if let Some(&index) = self.constant_indices.get(&key) {
+            return index;
+        }
+        self.constants.push(constant);
+        self.constant_indices.insert(key, self.constants.len() - 1);
+        self.constants.len() - 1
+    }
+    
+    fn emit_constant(&mut self, constant: Constant) {
+        let index = self.add_constant(constant);
+        self.emit(Opcode::LoadConst(index));
+    }
+    
     fn emit_jump(&mut self, instruction: Opcode) -> usize {
-        self.bytecode.push(instruction);
+        let instruction = match instruction {
+            Opcode::Jump(_) if self.relative_jumps => Opcode::JumpRel(0),
+            Opcode::JumpIfFalse(_) if self.relative_jumps => Opcode::JumpIfFalseRel(0),
+            Opcode::JumpIfTrue(_) if self.relative_jumps => Opcode::JumpIfTrueRel(0),
+            other => other,
+        };
+        self.emit(instruction);
         self.bytecode.len() - 1
     }
     
-    fn patch_jump(&mut self, offset: usize) {
+    /// Points the jump at `offset` to the next opcode to be emitted.
+    /// Only a compiler bug can reach the error path; it is reported rather
+    /// than panicking so embedders are never taken down with it.
+    fn patch_jump(&mut self, offset: usize) -> Result<(), ASError> {
         let jump = self.bytecode.len();
-        match &mut self.bytecode[offset] {
-            Opcode::JumpIfFalse(ref mut val) => *val = jump,
-            Opcode::Jump(ref mut val) => *val = jump,
-            _ => panic!("Attempted to patch non-jump"),
+        match self.bytecode.get_mut(offset) {
+            Some(Opcode::JumpIfFalse(ref mut val)) | Some(Opcode::JumpIfTrue(ref mut val)) | Some(Opcode::Jump(ref mut val)) => {
+                *val = jump;
+                Ok(())
+            }
+            Some(Opcode::JumpIfFalseRel(ref mut val)) | Some(Opcode::JumpIfTrueRel(ref mut val)) | Some(Opcode::JumpRel(ref mut val)) => {
+                *val = jump as isize - offset as isize;
+                Ok(())
+            }
+            other => {
+                let found = other.map_or_else(|| "nothing".to_string(), |op| format!("{:?}", op));
+                Err(self.error(&format!("Internal compiler error: cannot patch {} at {} as a jump", found, offset)))
+            }
         }
     }
     
+    /// Closes the innermost loop, pointing its `break`s at the next opcode.
+    fn patch_breaks(&mut self) -> Result<(), ASError> {
+        let context = self.loops.pop().unwrap_or_default();
+        for jump in context.break_jumps {
+            self.patch_jump(jump)?;
+        }
+        Ok(())
+    }
+
     fn emit_loop(&mut self, loop_start: usize) {
-        self.bytecode.push(Opcode::Jump(loop_start));
+        if self.relative_jumps {
+            let offset = loop_start as isize - self.bytecode.len() as isize;
+            self.emit(Opcode::JumpRel(offset));
+        } else {
+            self.emit(Opcode::Jump(loop_start));
+        }
     }
 
-    #[allow(dead_code)]
-    fn serialize_opcode(&self, opcode: &Opcode, bytes: &mut Vec<u8>) {
-        // Serialization format needs update for all opcodes
-        // 1: LoadConst(f64)
-        // 2: LoadString(len, bytes)
-        // ...
-        // This serialization is critical for runtime compatibility
-        // For simplicity, I'm just putting basics here to pass build
-        // Real implementation needs robust serialization
-        match opcode {
-            Opcode::LoadConst(n) => { bytes.push(1); bytes.extend(&n.to_le_bytes()); }
-            Opcode::LoadString(s) => { 
-                bytes.push(2); 
-                bytes.extend(&(s.len() as u32).to_le_bytes()); 
-                bytes.extend(s.as_bytes()); 
-            }
-            Opcode::Output => { bytes.push(8); }
-            _ => {} // ToDo: Implement all
+    /// One line per opcode, e.g. `0001 JumpIfFalse 0007`. Jump targets,
+    /// relative ones included, are shown as absolute indices.
+    pub fn disassemble(bytecode: &[Opcode]) -> String {
+        let target = |pc: usize, offset: isize| match pc.checked_add_signed(offset) {
+            Some(target) => format!("{:04}", target),
+            None => format!("<invalid {:+}>", offset),
+        };
+        let mut listing = String::new();
+        for (pc, opcode) in bytecode.iter().enumerate() {
+            let text = match opcode {
+                Opcode::LoadConst(index) => format!("LoadConst {}", index),
+                Opcode::LoadVar(name) => format!("LoadVar {}", name),
+                Opcode::StoreVar(name) => format!("StoreVar {}", name),
+                Opcode::LoadLocal(slot) => format!("LoadLocal {}", slot),
+                Opcode::StoreLocal(slot) => format!("StoreLocal {}", slot),
+                Opcode::Call(name, argc) => format!("Call {} {}", name, argc),
+                Opcode::CallFunction(index, argc) => format!("CallFunction {} {}", index, argc),
+                Opcode::LoadFunction(index) => format!("LoadFunction {}", index),
+                Opcode::MakeArray(count) => format!("MakeArray {}", count),
+                Opcode::MakeMap(count) => format!("MakeMap {}", count),
+                Opcode::Output(newline) => format!("Output {}", newline),
+                Opcode::OutputStr(index, newline) => format!("OutputStr {} {}", index, newline),
+                Opcode::Input(prompt) => format!("Input {}", prompt),
+                Opcode::Import(path) => format!("Import {:?}", path),
+                Opcode::ImportNames(path, names) => format!("ImportNames {:?} {}", path, names.join(", ")),
+                Opcode::Jump(to) => format!("Jump {:04}", to),
+                Opcode::JumpIfFalse(to) => format!("JumpIfFalse {:04}", to),
+                Opcode::JumpIfTrue(to) => format!("JumpIfTrue {:04}", to),
+                Opcode::JumpRel(offset) => format!("JumpRel {}", target(pc, *offset)),
+                Opcode::JumpIfFalseRel(offset) => format!("JumpIfFalseRel {}", target(pc, *offset)),
+                Opcode::JumpIfTrueRel(offset) => format!("JumpIfTrueRel {}", target(pc, *offset)),
+                other => format!("{:?}", other),
+            };
+            listing.push_str(&format!("{:04} {}\n", pc, text));
         }
+        listing
     }
-    
+
     fn error(&self, msg: &str) -> ASError {
-        ASError::new(ErrorKind::SyntaxError, msg.to_string(), SourceLocation::new(0,0))
+        ASError::new(ErrorKind::SyntaxError, msg.to_string(), self.location.clone())
     }
 }
\ No newline at end of file
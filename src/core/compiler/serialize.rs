@@ -0,0 +1,325 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+//! Binary encoding of compiled bytecode, as stored in `.asc` files.
+//!
+//! Every encoding starts with `MAGIC` and `FORMAT_VERSION`. Integers are
+//! little-endian; strings are a `u32` byte length followed by UTF-8.
+
+use super::{CodeObject, Constant, Opcode, Program};
+use crate::error::{ASError, ErrorKind, SourceLocation};
+use std::rc::Rc;
+
+/// Leading bytes of every encoding.
+pub const MAGIC: &[u8; 4] = b"ASC\0";
+/// Bumped whenever the encoding changes incompatibly.
+pub const FORMAT_VERSION: u8 = 2;
+
+/// Encodes a bare opcode sequence.
+pub fn serialize(bytecode: &[Opcode]) -> Vec<u8> {
+    let mut writer = Writer::with_header();
+    writer.opcodes(bytecode);
+    writer.bytes
+}
+
+/// Decodes the output of `serialize`.
+pub fn deserialize(bytes: &[u8]) -> Result<Vec<Opcode>, ASError> {
+    let mut reader = Reader::with_header(bytes)?;
+    let bytecode = reader.opcodes()?;
+    reader.finish()?;
+    Ok(bytecode)
+}
+
+/// Encodes a whole program: its main code and function table.
+pub fn serialize_program(program: &Program) -> Vec<u8> {
+    let mut writer = Writer::with_header();
+    writer.code_object(&program.main);
+    writer.u32(program.functions.len());
+    for function in &program.functions {
+        writer.code_object(function);
+    }
+    writer.bytes
+}
+
+/// Decodes the output of `serialize_program`.
+pub fn deserialize_program(bytes: &[u8]) -> Result<Program, ASError> {
+    let mut reader = Reader::with_header(bytes)?;
+    let main = reader.code_object()?;
+    let count = reader.u32()?;
+    let functions = (0..count).map(|_| reader.code_object()).collect::<Result<_, _>>()?;
+    reader.finish()?;
+    Ok(Program { main, functions })
+}
+
+fn error(message: &str) -> ASError {
+    ASError::new(ErrorKind::IOError, format!("Invalid bytecode: {}", message), SourceLocation::new(0, 0))
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn with_header() -> Self {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(FORMAT_VERSION);
+        Writer { bytes }
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u32(&mut self, value: usize) {
+        self.bytes.extend((value as u32).to_le_bytes());
+    }
+
+    fn i64(&mut self, value: i64) {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    fn string(&mut self, value: &str) {
+        self.u32(value.len());
+        self.bytes.extend(value.as_bytes());
+    }
+
+    fn code_object(&mut self, code: &CodeObject) {
+        self.string(&code.name);
+        self.u32(code.params.len());
+        for param in &code.params {
+            self.string(param);
+        }
+        self.u32(code.constants.len());
+        for constant in &code.constants {
+            self.constant(constant);
+        }
+        self.opcodes(&code.bytecode);
+        self.u32(code.lines.len());
+        for location in &code.lines {
+            self.u32(location.line);
+            self.u32(location.column);
+        }
+    }
+
+    fn constant(&mut self, constant: &Constant) {
+        match constant {
+            Constant::Integer(n) => { self.u8(0); self.i64(*n); }
+            Constant::Number(n) => { self.u8(1); self.bytes.extend(n.to_bits().to_le_bytes()); }
+            Constant::String(s) => { self.u8(2); self.string(s); }
+            Constant::Char(c) => { self.u8(3); self.u32(*c as usize); }
+            Constant::Boolean(b) => { self.u8(4); self.bool(*b); }
+        }
+    }
+
+    fn opcodes(&mut self, bytecode: &[Opcode]) {
+        self.u32(bytecode.len());
+        for opcode in bytecode {
+            self.opcode(opcode);
+        }
+    }
+
+    fn opcode(&mut self, opcode: &Opcode) {
+        match opcode {
+            Opcode::LoadConst(index) => { self.u8(0); self.u32(*index); }
+            Opcode::LoadVar(name) => { self.u8(1); self.string(name); }
+            Opcode::StoreVar(name) => { self.u8(2); self.string(name); }
+            Opcode::Call(name, argc) => { self.u8(3); self.string(name); self.u32(*argc); }
+            Opcode::CallFunction(index, argc) => { self.u8(4); self.u32(*index); self.u32(*argc); }
+            Opcode::LoadFunction(index) => { self.u8(5); self.u32(*index); }
+            Opcode::MakeArray(count) => { self.u8(6); self.u32(*count); }
+            Opcode::MakeMap(count) => { self.u8(7); self.u32(*count); }
+            Opcode::GetIndex => self.u8(8),
+            Opcode::SetIndex => self.u8(9),
+            Opcode::Return => self.u8(10),
+            Opcode::Output(newline) => { self.u8(11); self.bool(*newline); }
+            Opcode::OutputStr(index, newline) => { self.u8(12); self.u32(*index); self.bool(*newline); }
+            Opcode::Input(prompt) => { self.u8(13); self.bool(*prompt); }
+            Opcode::Import(path) => { self.u8(14); self.string(path); }
+            Opcode::Add => self.u8(15),
+            Opcode::Subtract => self.u8(16),
+            Opcode::Multiply => self.u8(17),
+            Opcode::Divide => self.u8(18),
+            Opcode::Modulo => self.u8(19),
+            Opcode::Power => self.u8(20),
+            Opcode::Eq => self.u8(21),
+            Opcode::Ne => self.u8(22),
+            Opcode::Lt => self.u8(23),
+            Opcode::Le => self.u8(24),
+            Opcode::Gt => self.u8(25),
+            Opcode::Ge => self.u8(26),
+            Opcode::And => self.u8(27),
+            Opcode::Or => self.u8(28),
+            Opcode::Not => self.u8(29),
+            Opcode::Negate => self.u8(30),
+            Opcode::BitwiseNot => self.u8(31),
+            Opcode::Jump(target) => { self.u8(32); self.u32(*target); }
+            Opcode::JumpIfFalse(target) => { self.u8(33); self.u32(*target); }
+            Opcode::JumpIfTrue(target) => { self.u8(34); self.u32(*target); }
+            Opcode::JumpRel(offset) => { self.u8(35); self.i64(*offset as i64); }
+            Opcode::JumpIfFalseRel(offset) => { self.u8(36); self.i64(*offset as i64); }
+            Opcode::JumpIfTrueRel(offset) => { self.u8(37); self.i64(*offset as i64); }
+            Opcode::Pop => self.u8(38),
+            Opcode::LoadLocal(slot) => { self.u8(39); self.u32(*slot); }
+            Opcode::StoreLocal(slot) => { self.u8(40); self.u32(*slot); }
+            Opcode::ImportNames(path, names) => {
+                self.u8(41);
+                self.string(path);
+                self.u32(names.len());
+                for name in names {
+                    self.string(name);
+                }
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn with_header(bytes: &'a [u8]) -> Result<Self, ASError> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(error("missing file header"));
+        }
+        let mut reader = Reader { bytes, position: MAGIC.len() };
+        let version = reader.u8()?;
+        if version != FORMAT_VERSION {
+            return Err(error(&format!("format version {} is not supported (expected {})", version, FORMAT_VERSION)));
+        }
+        Ok(reader)
+    }
+
+    fn finish(&self) -> Result<(), ASError> {
+        if self.position != self.bytes.len() {
+            return Err(error("unexpected data after the end"));
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ASError> {
+        let end = self.position.checked_add(count).filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| error("unexpected end of data"))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, ASError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<usize, ASError> {
+        let bytes = self.take(4)?.try_into().unwrap();
+        Ok(u32::from_le_bytes(bytes) as usize)
+    }
+
+    fn i64(&mut self) -> Result<i64, ASError> {
+        let bytes = self.take(8)?.try_into().unwrap();
+        Ok(i64::from_le_bytes(bytes))
+    }
+
+    fn bool(&mut self) -> Result<bool, ASError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(error(&format!("invalid boolean {}", other))),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ASError> {
+        let length = self.u32()?;
+        let bytes = self.take(length)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| error("string is not valid UTF-8"))
+    }
+
+    fn code_object(&mut self) -> Result<CodeObject, ASError> {
+        let name = self.string()?;
+        let param_count = self.u32()?;
+        let params = (0..param_count).map(|_| self.string()).collect::<Result<_, _>>()?;
+        let mut code = CodeObject::new(&name, params);
+        let constant_count = self.u32()?;
+        code.constants = (0..constant_count).map(|_| self.constant()).collect::<Result<_, _>>()?;
+        code.bytecode = self.opcodes()?;
+        let line_count = self.u32()?;
+        code.lines = (0..line_count)
+            .map(|_| Ok(SourceLocation::new(self.u32()?, self.u32()?)))
+            .collect::<Result<_, ASError>>()?;
+        Ok(code)
+    }
+
+    fn constant(&mut self) -> Result<Constant, ASError> {
+        Ok(match self.u8()? {
+            0 => Constant::Integer(self.i64()?),
+            1 => Constant::Number(f64::from_bits(self.i64()? as u64)),
+            2 => Constant::String(Rc::from(self.string()?.as_str())),
+            3 => {
+                let code = self.u32()? as u32;
+                Constant::Char(char::from_u32(code).ok_or_else(|| error(&format!("invalid character {}", code)))?)
+            }
+            4 => Constant::Boolean(self.bool()?),
+            other => return Err(error(&format!("unknown constant tag {}", other))),
+        })
+    }
+
+    fn opcodes(&mut self) -> Result<Vec<Opcode>, ASError> {
+        let count = self.u32()?;
+        (0..count).map(|_| self.opcode()).collect()
+    }
+
+    fn opcode(&mut self) -> Result<Opcode, ASError> {
+        Ok(match self.u8()? {
+            0 => Opcode::LoadConst(self.u32()?),
+            1 => Opcode::LoadVar(self.string()?),
+            2 => Opcode::StoreVar(self.string()?),
+            3 => Opcode::Call(self.string()?, self.u32()?),
+            4 => Opcode::CallFunction(self.u32()?, self.u32()?),
+            5 => Opcode::LoadFunction(self.u32()?),
+            6 => Opcode::MakeArray(self.u32()?),
+            7 => Opcode::MakeMap(self.u32()?),
+            8 => Opcode::GetIndex,
+            9 => Opcode::SetIndex,
+            10 => Opcode::Return,
+            11 => Opcode::Output(self.bool()?),
+            12 => Opcode::OutputStr(self.u32()?, self.bool()?),
+            13 => Opcode::Input(self.bool()?),
+            14 => Opcode::Import(self.string()?),
+            15 => Opcode::Add,
+            16 => Opcode::Subtract,
+            17 => Opcode::Multiply,
+            18 => Opcode::Divide,
+            19 => Opcode::Modulo,
+            20 => Opcode::Power,
+            21 => Opcode::Eq,
+            22 => Opcode::Ne,
+            23 => Opcode::Lt,
+            24 => Opcode::Le,
+            25 => Opcode::Gt,
+            26 => Opcode::Ge,
+            27 => Opcode::And,
+            28 => Opcode::Or,
+            29 => Opcode::Not,
+            30 => Opcode::Negate,
+            31 => Opcode::BitwiseNot,
+            32 => Opcode::Jump(self.u32()?),
+            33 => Opcode::JumpIfFalse(self.u32()?),
+            34 => Opcode::JumpIfTrue(self.u32()?),
+            35 => Opcode::JumpRel(self.i64()? as isize),
+            36 => Opcode::JumpIfFalseRel(self.i64()? as isize),
+            37 => Opcode::JumpIfTrueRel(self.i64()? as isize),
+            38 => Opcode::Pop,
+            39 => Opcode::LoadLocal(self.u32()?),
+            40 => Opcode::StoreLocal(self.u32()?),
+            41 => {
+                let path = self.string()?;
+                let count = self.u32()?;
+                Opcode::ImportNames(path, (0..count).map(|_| self.string()).collect::<Result<_, _>>()?)
+            }
+            other => return Err(error(&format!("unknown opcode tag {}", other))),
+        })
+    }
+}
@@ -1,6 +1,8 @@
 // Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
 
-#[derive(Debug, Clone, PartialEq)]
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct SourceLocation {
     pub line: usize,
     pub column: usize,
@@ -17,6 +19,38 @@ impl SourceLocation {
     }
 }
 
+/// The source range covered by a token or statement.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Span {
+    pub start: SourceLocation,
+    pub end: SourceLocation,
+}
+
+impl Span {
+    pub fn new(start: SourceLocation, end: SourceLocation) -> Self {
+        Span { start, end }
+    }
+}
+
+/// A non-fatal diagnostic, such as a name redeclared in the same scope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub message: String,
+    pub location: SourceLocation,
+    /// Where the conflicting earlier definition lives, if any.
+    pub previous: Option<SourceLocation>,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "[{}:{}] Warning: {}", self.location.line, self.location.column, self.message)?;
+        if let Some(ref previous) = self.previous {
+            write!(f, " (previously defined at [{}:{}])", previous.line, previous.column)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ErrorKind {
     SyntaxError,
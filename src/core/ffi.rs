@@ -2,11 +2,62 @@
 
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
-use crate::runtime;
+use crate::error::{ASError, ErrorKind, SourceLocation};
+use crate::runtime::{self, Runtime};
+
+/// Status of an `AsResult` whose code ran successfully.
+pub const AS_OK: i32 = 0;
+/// Status of an `AsResult` whose code was null or not valid UTF-8.
+pub const AS_INVALID_INPUT: i32 = 7;
+
+/// The outcome of `as_execute_result`. `status` is `AS_OK` or the error
+/// code of the failure (see `status_code`); exactly one of `output` and
+/// `message` is non-null. Free it with `as_free_result`.
+#[repr(C)]
+pub struct AsResult {
+    pub status: i32,
+    pub output: *mut c_char,
+    pub message: *mut c_char,
+}
+
+/// The nonzero status reported for errors of `kind`.
+pub fn status_code(kind: &ErrorKind) -> i32 {
+    match kind {
+        ErrorKind::SyntaxError => 1,
+        ErrorKind::TypeError => 2,
+        ErrorKind::RuntimeError => 3,
+        ErrorKind::UndefinedVariable => 4,
+        ErrorKind::UndefinedFunction => 5,
+        ErrorKind::IOError => 6,
+    }
+}
+
+/// Copies `s` into a C string, dropping any interior NUL bytes.
+fn to_c_string(s: String) -> *mut c_char {
+    CString::new(s.replace('\0', "")).unwrap().into_raw()
+}
+
+/// A runtime for C callers, which get the output back as a string and so
+/// must not also see it on the process's standard output.
+fn embedded_runtime() -> Runtime {
+    let mut runtime = Runtime::new();
+    runtime.set_output_writer(std::io::sink());
+    runtime
+}
+
+fn error_result(status: i32, message: String) -> AsResult {
+    AsResult { status, output: std::ptr::null_mut(), message: to_c_string(message) }
+}
 
 /// Execute AS Lang code and return the output as a C string.
 /// The caller is responsible for freeing the returned string using `as_free_string`.
+/// Errors come back as text starting with "Error:"; callers that need to tell
+/// them apart should use `as_execute_result`.
+///
+/// `code` must be null or a valid pointer to a NUL-terminated string. The
+/// function stays safe to call from Rust, as it always has been.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn as_execute(code: *const c_char) -> *mut c_char {
     if code.is_null() {
         return std::ptr::null_mut();
@@ -29,7 +80,10 @@ pub extern "C" fn as_execute(code: *const c_char) -> *mut c_char {
 }
 
 /// Free a string returned by `as_execute`.
+///
+/// `s` must be null or a pointer previously returned by `as_execute`.
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn as_free_string(s: *mut c_char) {
     if s.is_null() {
         return;
@@ -38,3 +92,123 @@ pub extern "C" fn as_free_string(s: *mut c_char) {
         let _ = CString::from_raw(s);
     }
 }
+
+/// Execute AS Lang code and return its status along with the output or the
+/// error message, so callers can branch on `status` without parsing text.
+///
+/// # Safety
+/// `code` must be null or a valid pointer to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn as_execute_result(code: *const c_char) -> AsResult {
+    if code.is_null() {
+        return error_result(AS_INVALID_INPUT, "Code is null".to_string());
+    }
+    let input = match unsafe { CStr::from_ptr(code) }.to_str() {
+        Ok(str) => str,
+        Err(_) => return error_result(AS_INVALID_INPUT, "Invalid UTF-8".to_string()),
+    };
+
+    match embedded_runtime().execute(input) {
+        Ok(output) => AsResult { status: AS_OK, output: to_c_string(output), message: std::ptr::null_mut() },
+        Err(e) => error_result(status_code(&e.kind), e.to_string()),
+    }
+}
+
+/// Free the strings held by a result returned by `as_execute_result`.
+///
+/// # Safety
+/// `result` must have been returned by `as_execute_result` and not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn as_free_result(result: AsResult) {
+    as_free_string(result.output);
+    as_free_string(result.message);
+}
+
+/// Create a runtime whose variables and functions persist across calls to
+/// `as_runtime_execute`. Release it with `as_runtime_free`.
+#[no_mangle]
+pub extern "C" fn as_runtime_new() -> *mut Runtime {
+    Box::into_raw(Box::new(embedded_runtime()))
+}
+
+/// Execute AS Lang code on `handle` and return the output as a C string,
+/// formatted like `as_execute`. Returns null if `handle` or `code` is null.
+///
+/// # Safety
+/// `handle` must be null or a live pointer from `as_runtime_new`, and `code`
+/// must be null or a valid pointer to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn as_runtime_execute(handle: *mut Runtime, code: *const c_char) -> *mut c_char {
+    if handle.is_null() || code.is_null() {
+        return std::ptr::null_mut();
+    }
+    let runtime = unsafe { &mut *handle };
+    let input = match unsafe { CStr::from_ptr(code) }.to_str() {
+        Ok(str) => str,
+        Err(_) => return to_c_string("Error: Invalid UTF-8".to_string()),
+    };
+
+    match runtime.execute(input) {
+        Ok(output) => to_c_string(output),
+        Err(e) => to_c_string(format!("Error: {}", e)),
+    }
+}
+
+/// Stop each later `as_runtime_execute` on `handle` with an error after
+/// `limit` opcodes, so untrusted code can't loop forever. A limit of 0
+/// removes it.
+///
+/// # Safety
+/// `handle` must be null or a live pointer from `as_runtime_new`.
+#[no_mangle]
+pub unsafe extern "C" fn as_runtime_set_instruction_limit(handle: *mut Runtime, limit: u64) {
+    if handle.is_null() {
+        return;
+    }
+    let runtime = unsafe { &mut *handle };
+    runtime.set_instruction_limit(if limit == 0 { None } else { Some(limit) });
+}
+
+/// Free a runtime created by `as_runtime_new`.
+///
+/// # Safety
+/// `handle` must be null or a pointer from `as_runtime_new` not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn as_runtime_free(handle: *mut Runtime) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// A host function that supplies a line for an `input` statement. It gets
+/// the prompt ("" when there is none) and returns the line, or null when no
+/// input is left. The runtime copies the line and never frees it.
+pub type InputCallback = extern "C" fn(prompt: *const c_char) -> *mut c_char;
+
+/// Read `input` lines on `handle` from `callback` instead of standard input.
+/// Passing a null callback goes back to standard input.
+///
+/// # Safety
+/// `handle` must be null or a live pointer from `as_runtime_new`.
+#[no_mangle]
+pub unsafe extern "C" fn as_set_input_callback(handle: *mut Runtime, callback: Option<InputCallback>) {
+    if handle.is_null() {
+        return;
+    }
+    let runtime = unsafe { &mut *handle };
+    let Some(callback) = callback else {
+        runtime.clear_input_handler();
+        return;
+    };
+    runtime.set_input_handler(move |prompt| {
+        let prompt = CString::new(prompt.replace('\0', "")).unwrap();
+        let line = callback(prompt.as_ptr());
+        if line.is_null() {
+            return Err(ASError::new(ErrorKind::IOError, "Could not read input: end of input".to_string(), SourceLocation::new(0, 0)));
+        }
+        Ok(unsafe { CStr::from_ptr(line) }.to_string_lossy().into_owned())
+    });
+}
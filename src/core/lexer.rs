@@ -1,33 +1,122 @@
 // Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
 
-use crate::error::{ASError, ErrorKind, SourceLocation};
+use crate::error::{ASError, ErrorKind, SourceLocation, Span};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     // Literals
     Number(f64),
     String(String),
+    /// A string literal containing `${...}` interpolations.
+    InterpolatedString(Vec<StringPart>),
+    Char(char),
     Identifier(String),
     Boolean(bool),
     
     // Keywords
     Let, Fn, If, Else, ElseIf, While, For, Do, 
-    Break, Continue, Return, Output, Input, Import,
+    Break, Continue, Return, Output, Input, Import, From,
     
     // Operators
     Plus, Minus, Star, Slash, Percent, Caret,
+    PlusEq, MinusEq, StarEq, SlashEq,
     Eq, EqEq, Ne, Lt, Le, Gt, Ge,
-    And, Or, Not, BitwiseAnd, BitwiseOr,
+    And, Or, Not, BitwiseAnd, BitwiseOr, BitwiseNot,
     LeftShift, RightShift,
     
     // Delimiters
     LParen, RParen, LBrace, RBrace, LBracket, RBracket,
-    Comma, Semicolon, Colon,
+    Comma, Semicolon, Colon, Dot, DotDot, Arrow,
     
     // Special
     Inc, Dec, EOF,
 }
 
+impl Token {
+    /// The variant's name without its payload, e.g. `"Identifier"`. Tools
+    /// such as the WASM `tokenize` expose it, so it is spelled out here
+    /// rather than taken from `Debug`.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Token::Number(_) => "Number",
+            Token::String(_) => "String",
+            Token::InterpolatedString(_) => "InterpolatedString",
+            Token::Char(_) => "Char",
+            Token::Identifier(_) => "Identifier",
+            Token::Boolean(_) => "Boolean",
+            Token::Let => "Let",
+            Token::Fn => "Fn",
+            Token::If => "If",
+            Token::Else => "Else",
+            Token::ElseIf => "ElseIf",
+            Token::While => "While",
+            Token::For => "For",
+            Token::Do => "Do",
+            Token::Break => "Break",
+            Token::Continue => "Continue",
+            Token::Return => "Return",
+            Token::Output => "Output",
+            Token::Input => "Input",
+            Token::Import => "Import",
+            Token::From => "From",
+            Token::Plus => "Plus",
+            Token::Minus => "Minus",
+            Token::Star => "Star",
+            Token::Slash => "Slash",
+            Token::Percent => "Percent",
+            Token::Caret => "Caret",
+            Token::PlusEq => "PlusEq",
+            Token::MinusEq => "MinusEq",
+            Token::StarEq => "StarEq",
+            Token::SlashEq => "SlashEq",
+            Token::Eq => "Eq",
+            Token::EqEq => "EqEq",
+            Token::Ne => "Ne",
+            Token::Lt => "Lt",
+            Token::Le => "Le",
+            Token::Gt => "Gt",
+            Token::Ge => "Ge",
+            Token::And => "And",
+            Token::Or => "Or",
+            Token::Not => "Not",
+            Token::BitwiseAnd => "BitwiseAnd",
+            Token::BitwiseOr => "BitwiseOr",
+            Token::BitwiseNot => "BitwiseNot",
+            Token::LeftShift => "LeftShift",
+            Token::RightShift => "RightShift",
+            Token::LParen => "LParen",
+            Token::RParen => "RParen",
+            Token::LBrace => "LBrace",
+            Token::RBrace => "RBrace",
+            Token::LBracket => "LBracket",
+            Token::RBracket => "RBracket",
+            Token::Comma => "Comma",
+            Token::Semicolon => "Semicolon",
+            Token::Colon => "Colon",
+            Token::Dot => "Dot",
+            Token::DotDot => "DotDot",
+            Token::Arrow => "Arrow",
+            Token::Inc => "Inc",
+            Token::Dec => "Dec",
+            Token::EOF => "EOF",
+        }
+    }
+}
+
+/// A piece of an interpolated string literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Literal(String),
+    /// The tokens of an embedded `${...}` expression, ending with `EOF`.
+    Expression(Vec<(Token, Span)>),
+}
+
+/// Words `read_identifier` turns into keyword or literal tokens.
+pub const KEYWORDS: &[&str] = &[
+    "let", "fn", "if", "else", "elseif", "while", "for", "do", "break", "continue",
+    "return", "output", "input", "import", "from", "and", "or", "not", "true", "false",
+];
+
 pub struct Lexer<'a> {
     #[allow(dead_code)]
     input: &'a str,
@@ -49,10 +138,45 @@ impl<'a> Lexer<'a> {
     }
 
     pub fn tokenize(&mut self) -> Result<Vec<Token>, ASError> {
+        Ok(self.tokenize_spanned()?.into_iter().map(|(token, _)| token).collect())
+    }
+    
+    /// Tokenize the input, pairing every token with the source range it came from.
+    ///
+    /// Newlines are whitespace: statements end at `;`, so an expression may
+    /// break across lines anywhere, including after a trailing operator or
+    /// before a leading one. A `\` at the end of a line is also accepted as
+    /// an explicit continuation, which stays valid if line breaks ever
+    /// become significant.
+    pub fn tokenize_spanned(&mut self) -> Result<Vec<(Token, Span)>, ASError> {
         let mut tokens = Vec::new();
+        let mut spans = Vec::new();
+        self.scan(&mut tokens, &mut spans)?;
+        Ok(tokens.into_iter().zip(spans).collect())
+    }
+    
+    /// Like `tokenize_spanned`, but on an error returns the tokens read
+    /// before it (without a trailing `EOF`) along with the error, so editors
+    /// can still highlight the valid prefix.
+    pub fn tokenize_partial(&mut self) -> (Vec<(Token, Span)>, Option<ASError>) {
+        let mut tokens = Vec::new();
+        let mut spans = Vec::new();
+        let error = self.scan(&mut tokens, &mut spans).err();
+        (tokens.into_iter().zip(spans).collect(), error)
+    }
+    
+    /// Reads tokens and their spans into `tokens` and `spans`, which stay the
+    /// same length even when an error stops the scan.
+    fn scan(&mut self, tokens: &mut Vec<Token>, spans: &mut Vec<Span>) -> Result<(), ASError> {
+        // A leading `#!` line lets scripts be run directly
+        if self.position == 0 && self.chars.starts_with(&['#', '!']) {
+            self.skip_comment();
+        }
         
         while self.position < self.chars.len() {
             let ch = self.chars[self.position];
+            let start = SourceLocation::new(self.line, self.column);
+            let count = tokens.len();
             
             match ch {
                 // Whitespace
@@ -62,6 +186,24 @@ impl<'a> Lexer<'a> {
                 '\n' => {
                     self.advance_line();
                 }
+                // Explicit line continuation: `\` before the end of the line
+                '\\' => {
+                    let rest = &self.chars[self.position + 1..];
+                    let blanks = rest.iter().take_while(|&&c| c == ' ' || c == '\t' || c == '\r').count();
+                    if rest.get(blanks).is_some_and(|&c| c != '\n') {
+                        return Err(ASError::new(
+                            ErrorKind::SyntaxError,
+                            "Line continuation '\\' must be the last character on its line".to_string(),
+                            start,
+                        ));
+                    }
+                    for _ in 0..=blanks {
+                        self.advance();
+                    }
+                    if self.position < self.chars.len() {
+                        self.advance_line();
+                    }
+                }
                 
                 // Delimiters
                 '(' => { tokens.push(Token::LParen); self.advance(); }
@@ -73,26 +215,45 @@ impl<'a> Lexer<'a> {
                 ',' => { tokens.push(Token::Comma); self.advance(); }
                 ';' => { tokens.push(Token::Semicolon); self.advance(); }
                 ':' => { tokens.push(Token::Colon); self.advance(); }
+                '.' => {
+                    // `.5` is a number; a bare `.` or `..` is a member/range token
+                    if self.peek_next().is_ascii_digit() { tokens.push(self.read_number()?); }
+                    else if self.match_next('.') { tokens.push(Token::DotDot); }
+                    else { tokens.push(Token::Dot); }
+                }
                 
                 // Operators
                 '+' => {
-                    if self.match_next('+') { tokens.push(Token::Inc); }
+                    if self.peek_next() == '=' { tokens.push(Token::PlusEq); self.advance(); self.advance(); }
+                    else if self.match_next('+') { tokens.push(Token::Inc); }
                     else { tokens.push(Token::Plus); }
                 }
                 '-' => {
-                    if self.match_next('-') { tokens.push(Token::Dec); }
+                    if self.peek_next() == '=' { tokens.push(Token::MinusEq); self.advance(); self.advance(); }
+                    else if self.peek_next() == '>' { tokens.push(Token::Arrow); self.advance(); self.advance(); }
+                    else if self.match_next('-') { tokens.push(Token::Dec); }
                     else { tokens.push(Token::Minus); }
                 }
-                '*' => { tokens.push(Token::Star); self.advance(); }
+                '*' => {
+                    // `**` is another spelling of `^`
+                    if self.peek_next() == '*' { tokens.push(Token::Caret); self.advance(); self.advance(); }
+                    else if self.match_next('=') { tokens.push(Token::StarEq); }
+                    else { tokens.push(Token::Star); }
+                }
                 '/' => {
                     if self.peek_next() == '/' {
                         self.skip_comment();
+                    } else if self.peek_next() == '*' {
+                        self.skip_block_comment()?;
+                    } else if self.match_next('=') {
+                        tokens.push(Token::SlashEq);
                     } else {
-                        tokens.push(Token::Slash); self.advance();
+                        tokens.push(Token::Slash);
                     }
                 }
                 '%' => { tokens.push(Token::Percent); self.advance(); }
                 '^' => { tokens.push(Token::Caret); self.advance(); }
+                '~' => { tokens.push(Token::BitwiseNot); self.advance(); }
                 '=' => {
                     if self.match_next('=') { tokens.push(Token::EqEq); }
                     else { tokens.push(Token::Eq); }
@@ -102,14 +263,19 @@ impl<'a> Lexer<'a> {
                     else { tokens.push(Token::Not); }
                 }
                 '<' => {
-                    if self.match_next('=') { tokens.push(Token::Le); }
-                    else if self.match_next('<') { tokens.push(Token::LeftShift); }
-                    else { tokens.push(Token::Lt); }
+                    // match_next consumes the '<' even on a miss, so look ahead first
+                    match self.peek_next() {
+                        '=' => { tokens.push(Token::Le); self.advance(); self.advance(); }
+                        '<' => { tokens.push(Token::LeftShift); self.advance(); self.advance(); }
+                        _ => { tokens.push(Token::Lt); self.advance(); }
+                    }
                 }
                 '>' => {
-                    if self.match_next('=') { tokens.push(Token::Ge); }
-                    else if self.match_next('>') { tokens.push(Token::RightShift); }
-                    else { tokens.push(Token::Gt); }
+                    match self.peek_next() {
+                        '=' => { tokens.push(Token::Ge); self.advance(); self.advance(); }
+                        '>' => { tokens.push(Token::RightShift); self.advance(); self.advance(); }
+                        _ => { tokens.push(Token::Gt); self.advance(); }
+                    }
                 }
                 '&' => {
                     if self.match_next('&') { tokens.push(Token::And); }
@@ -122,9 +288,10 @@ impl<'a> Lexer<'a> {
                 
                 // Strings
                 '"' => tokens.push(self.read_string()?),
+                '\'' => tokens.push(self.read_char()?),
                 
                 // Numbers
-                c if c.is_digit(10) => tokens.push(self.read_number()?),
+                c if c.is_ascii_digit() => tokens.push(self.read_number()?),
                 
                 // Identifiers and keywords
                 c if c.is_alphabetic() || c == '_' => tokens.push(self.read_identifier()),
@@ -137,10 +304,16 @@ impl<'a> Lexer<'a> {
                     ));
                 }
             }
+            
+            if tokens.len() > count {
+                spans.push(Span::new(start, SourceLocation::new(self.line, self.column)));
+            }
         }
         
+        let end = SourceLocation::new(self.line, self.column);
         tokens.push(Token::EOF);
-        Ok(tokens)
+        spans.push(Span::new(end.clone(), end));
+        Ok(())
     }
     
     fn advance(&mut self) {
@@ -155,10 +328,7 @@ impl<'a> Lexer<'a> {
     }
     
     fn match_next(&mut self, expected: char) -> bool {
-        if self.position + 1 >= self.chars.len() {
-            return false;
-        }
-        if self.chars[self.position + 1] == expected {
+        if self.peek_next() == expected {
             self.advance(); // consume current
             self.advance(); // consume next
             return true;
@@ -183,14 +353,55 @@ impl<'a> Lexer<'a> {
         // Don't consume newline, let main loop handle it to increment line counter
     }
     
+    /// Skips a `/* ... */` comment. Block comments nest, so commenting out
+    /// code that already contains one works as expected.
+    fn skip_block_comment(&mut self) -> Result<(), ASError> {
+        let start = SourceLocation::new(self.line, self.column);
+        let mut depth = 0;
+        while self.position < self.chars.len() {
+            let ch = self.chars[self.position];
+            if ch == '/' && self.peek_next() == '*' {
+                depth += 1;
+                self.advance();
+                self.advance();
+            } else if ch == '*' && self.peek_next() == '/' {
+                depth -= 1;
+                self.advance();
+                self.advance();
+                if depth == 0 {
+                    return Ok(());
+                }
+            } else if ch == '\n' {
+                self.advance_line();
+            } else {
+                self.advance();
+            }
+        }
+        Err(ASError::new(
+            ErrorKind::SyntaxError,
+            "Unterminated block comment".to_string(),
+            start,
+        ))
+    }
+    
     fn read_string(&mut self) -> Result<Token, ASError> {
         let start_line = self.line;
         let start_col = self.column;
         
         self.advance(); // Skip opening quote
         let mut value = String::new();
+        let mut parts = Vec::new();
         
         while self.position < self.chars.len() && self.chars[self.position] != '"' {
+            if self.chars[self.position] == '\\' {
+                value.push(self.read_escape()?);
+                continue;
+            }
+            if self.chars[self.position] == '$' && self.peek_next() == '{' {
+                parts.push(StringPart::Literal(std::mem::take(&mut value)));
+                parts.push(StringPart::Expression(self.read_interpolation()?));
+                continue;
+            }
             if self.chars[self.position] == '\n' {
                 self.line += 1;
                 self.column = 1;
@@ -210,19 +421,148 @@ impl<'a> Lexer<'a> {
         }
         
         self.advance(); // Skip closing quote
-        Ok(Token::String(value))
+        if parts.is_empty() {
+            return Ok(Token::String(value));
+        }
+        parts.push(StringPart::Literal(value));
+        Ok(Token::InterpolatedString(parts))
+    }
+    
+    /// Reads a `${...}` interpolation and tokenizes the expression inside.
+    /// Braces and string literals within the expression are balanced, so
+    /// `${ {"}": 1} }`-style nesting finds the right closing brace.
+    fn read_interpolation(&mut self) -> Result<Vec<(Token, Span)>, ASError> {
+        let start = SourceLocation::new(self.line, self.column);
+        self.advance(); // Skip '$'
+        self.advance(); // Skip '{'
+        let (line, column, begin) = (self.line, self.column, self.position);
+        let mut depth = 1;
+        let mut in_string = false;
+        while self.position < self.chars.len() {
+            let ch = self.chars[self.position];
+            match ch {
+                '\\' if in_string => self.advance(),
+                '"' => in_string = !in_string,
+                '{' if !in_string => depth += 1,
+                '}' if !in_string => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            if ch == '\n' { self.advance_line(); } else { self.advance(); }
+        }
+        if depth > 0 {
+            return Err(ASError::new(ErrorKind::SyntaxError, "Unterminated string interpolation".to_string(), start));
+        }
+        let chars = self.chars[begin..self.position].to_vec();
+        self.advance(); // Skip closing '}'
+        if chars.iter().all(|c| c.is_whitespace()) {
+            return Err(ASError::new(ErrorKind::SyntaxError, "Empty string interpolation".to_string(), start));
+        }
+        let mut inner = Lexer { input: "", chars, position: 0, line, column };
+        inner.tokenize_spanned()
+    }
+    
+    /// Reads a `'c'` literal holding exactly one character or escape.
+    fn read_char(&mut self) -> Result<Token, ASError> {
+        let start = SourceLocation::new(self.line, self.column);
+        self.advance(); // Skip opening quote
+        let mut chars = Vec::new();
+        
+        while self.position < self.chars.len() && !matches!(self.chars[self.position], '\'' | '\n') {
+            if self.chars[self.position] == '\\' {
+                chars.push(self.read_escape()?);
+            } else {
+                chars.push(self.chars[self.position]);
+                self.advance();
+            }
+        }
+        
+        if self.position >= self.chars.len() || self.chars[self.position] != '\'' {
+            return Err(ASError::new(ErrorKind::SyntaxError, "Unterminated character literal".to_string(), start));
+        }
+        self.advance(); // Skip closing quote
+        
+        match chars.as_slice() {
+            [c] => Ok(Token::Char(*c)),
+            [] => Err(ASError::new(ErrorKind::SyntaxError, "Empty character literal".to_string(), start)),
+            _ => Err(ASError::new(
+                ErrorKind::SyntaxError,
+                "Character literal must contain exactly one character".to_string(),
+                start,
+            )),
+        }
+    }
+    
+    /// Reads the escape sequence at a backslash. An unknown sequence such
+    /// as `\d` is not an error: the backslash is kept as written and the
+    /// character after it is read as usual, so `"C:\dir"` means what it says.
+    fn read_escape(&mut self) -> Result<char, ASError> {
+        let (line, column) = (self.line, self.column);
+        self.advance(); // Skip backslash
+        if self.position >= self.chars.len() {
+            return Err(ASError::new(
+                ErrorKind::SyntaxError,
+                "Unterminated escape sequence".to_string(),
+                SourceLocation::new(line, column),
+            ));
+        }
+        let escaped = match self.chars[self.position] {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '$' => '$',
+            '\'' => '\'',
+            _ => return Ok('\\'),
+        };
+        self.advance();
+        Ok(escaped)
     }
     
     fn read_number(&mut self) -> Result<Token, ASError> {
+        let start = SourceLocation::new(self.line, self.column);
+        if self.chars[self.position] == '0' {
+            let radix = match self.peek_next() {
+                'x' | 'X' => Some(16),
+                'b' | 'B' => Some(2),
+                'o' | 'O' => Some(8),
+                _ => None,
+            };
+            if let Some(radix) = radix {
+                return self.read_radix_number(radix, start);
+            }
+        }
         let mut value = String::new();
         let mut has_dot = false;
+        let mut malformed = false;
         
         while self.position < self.chars.len() {
             let ch = self.chars[self.position];
-            if ch.is_digit(10) {
+            if ch.is_ascii_digit() {
                 value.push(ch);
                 self.advance();
-            } else if ch == '.' && !has_dot {
+            } else if ch == '_' {
+                // Digit separators may only sit between two digits
+                if !self.is_separator_between(&value, 10) {
+                    malformed = true;
+                }
+                value.push(ch);
+                self.advance();
+            } else if ch == '.' && self.peek_next() != '.' {
+                // A second dot followed by a digit (`3.4.5`) is a malformed number,
+                // but `3.4.x` leaves the dot for member access
+                if has_dot {
+                    if !self.peek_next().is_ascii_digit() {
+                        break;
+                    }
+                    malformed = true;
+                }
                 has_dot = true;
                 value.push(ch);
                 self.advance();
@@ -231,16 +571,77 @@ impl<'a> Lexer<'a> {
             }
         }
         
-        match value.parse::<f64>() {
-            Ok(num) => Ok(Token::Number(num)),
-            Err(_) => Err(ASError::new(
+        // Exponent: `e`/`E`, an optional sign, then at least one digit
+        if !malformed && self.position < self.chars.len() && matches!(self.chars[self.position], 'e' | 'E') {
+            value.push(self.chars[self.position]);
+            self.advance();
+            if self.position < self.chars.len() && matches!(self.chars[self.position], '+' | '-') {
+                value.push(self.chars[self.position]);
+                self.advance();
+            }
+            let mut has_digits = false;
+            while self.position < self.chars.len() && (self.chars[self.position].is_ascii_digit() || self.chars[self.position] == '_') {
+                if self.chars[self.position] == '_' {
+                    malformed |= !self.is_separator_between(&value, 10);
+                } else {
+                    has_digits = true;
+                }
+                value.push(self.chars[self.position]);
+                self.advance();
+            }
+            malformed |= !has_digits;
+        }
+        
+        match value.replace('_', "").parse::<f64>() {
+            Ok(num) if !malformed => Ok(Token::Number(num)),
+            _ => Err(ASError::new(
                 ErrorKind::SyntaxError,
                 format!("Invalid number: {}", value),
-                SourceLocation::new(self.line, self.column),
+                start,
             )),
         }
     }
     
+    /// Reads a `0x`/`0b`/`0o` integer literal. The whole alphanumeric run is
+    /// consumed so that malformed literals like `0b12` are reported whole.
+    fn read_radix_number(&mut self, radix: u32, start: SourceLocation) -> Result<Token, ASError> {
+        let mut lexeme: String = self.chars[self.position..self.position + 2].iter().collect();
+        self.advance();
+        self.advance();
+        let mut value = 0.0;
+        let mut valid = true;
+        let mut digits = 0;
+        while self.position < self.chars.len() && (self.chars[self.position].is_ascii_alphanumeric() || self.chars[self.position] == '_') {
+            let ch = self.chars[self.position];
+            if ch == '_' {
+                valid &= self.is_separator_between(&lexeme, radix);
+            } else {
+                match ch.to_digit(radix) {
+                    Some(digit) => value = value * radix as f64 + digit as f64,
+                    None => valid = false,
+                }
+                digits += 1;
+            }
+            lexeme.push(ch);
+            self.advance();
+        }
+        if !valid || digits == 0 {
+            return Err(ASError::new(
+                ErrorKind::SyntaxError,
+                format!("Invalid number: {}", lexeme),
+                start,
+            ));
+        }
+        Ok(Token::Number(value))
+    }
+    
+    /// Whether the `_` at the current position separates a digit already
+    /// read into `lexeme` from a digit that follows.
+    fn is_separator_between(&self, lexeme: &str, radix: u32) -> bool {
+        let before = lexeme.chars().last().is_some_and(|c| c.is_digit(radix));
+        before && self.peek_next().is_digit(radix)
+    }
+    
     fn read_identifier(&mut self) -> Token {
         let mut value = String::new();
         
@@ -269,6 +670,10 @@ impl<'a> Lexer<'a> {
             "output" => Token::Output,
             "input" => Token::Input,
             "import" => Token::Import,
+            "from" => Token::From,
+            "and" => Token::And,
+            "or" => Token::Or,
+            "not" => Token::Not,
             "true" => Token::Boolean(true),
             "false" => Token::Boolean(false),
             _ => Token::Identifier(value),
@@ -12,6 +12,7 @@ pub mod lexer;
 pub mod types;
 pub mod resolver;
 pub mod ffi;
+pub mod analysis;
 
 pub use compiler::*;
 pub use parser::*;
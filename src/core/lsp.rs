@@ -1,16 +1,21 @@
-use lsp_server::{Connection, Message, Notification};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
 use serde_json::Value;
-use aslang::parser::Parser;
+use aslang::analysis::{self, CompletionKind, DocumentSymbol, SymbolKind};
+use aslang::error::{SourceLocation, Span};
+use std::collections::HashMap;
 use std::error::Error;
 
 pub fn start_lsp() -> Result<(), Box<dyn Error + Send + Sync>> {
     eprintln!("Starting ASLang LSP server...");
     let (connection, io_threads) = Connection::stdio();
 
-    let server_capabilities = serde_json::to_value(&serde_json::json!({
-        "capabilities": {
-            "textDocumentSync": 1 // Full synchronization
-        }
+    // `initialize` wraps these in the response's "capabilities" field
+    let server_capabilities = serde_json::to_value(serde_json::json!({
+        "textDocumentSync": 1, // Full synchronization
+        "hoverProvider": true,
+        "completionProvider": {},
+        "definitionProvider": true,
+        "documentSymbolProvider": true
     })).unwrap();
     
     let initialization_params = connection.initialize(server_capabilities)?;
@@ -21,12 +26,23 @@ pub fn start_lsp() -> Result<(), Box<dyn Error + Send + Sync>> {
 
 fn main_loop(connection: Connection, _params: Value) -> Result<(), Box<dyn Error + Send + Sync>> {
     eprintln!("LSP Loop Started");
+    // Latest text of each open document, by URI
+    let mut documents: HashMap<String, String> = HashMap::new();
     for msg in &connection.receiver {
         match msg {
             Message::Request(req) => {
                 if connection.handle_shutdown(&req)? {
                     return Ok(());
                 }
+                let response = match handle_request(&req, &documents) {
+                    Some(result) => Response::new_ok(req.id, result),
+                    None => Response::new_err(
+                        req.id,
+                        ErrorCode::MethodNotFound as i32,
+                        format!("Unsupported method: {}", req.method)
+                    ),
+                };
+                connection.sender.send(Message::Response(response))?;
             }
             Message::Response(_) => {}
             Message::Notification(not) => {
@@ -37,6 +53,7 @@ fn main_loop(connection: Connection, _params: Value) -> Result<(), Box<dyn Error
                             params["textDocument"]["text"].as_str()
                         ) {
                             validate_document(&connection, uri, text);
+                            documents.insert(uri.to_string(), text.to_string());
                         }
                     }
                 } else if not.method == "textDocument/didChange" {
@@ -48,10 +65,17 @@ fn main_loop(connection: Connection, _params: Value) -> Result<(), Box<dyn Error
                             if let Some(change) = changes.first() {
                                 if let Some(text) = change["text"].as_str() {
                                     validate_document(&connection, uri, text);
+                                    documents.insert(uri.to_string(), text.to_string());
                                 }
                             }
                         }
                     }
+                } else if not.method == "textDocument/didClose" {
+                    if let Some(uri) = not.params["textDocument"]["uri"].as_str() {
+                        documents.remove(uri);
+                        // A closed document's errors shouldn't linger in the editor
+                        publish_diagnostics(&connection, uri, Vec::new());
+                    }
                 }
             }
         }
@@ -59,26 +83,106 @@ fn main_loop(connection: Connection, _params: Value) -> Result<(), Box<dyn Error
     Ok(())
 }
 
-fn validate_document(connection: &Connection, uri: &str, text: &str) {
-    let diagnostics = match Parser::parse(text) {
-        Ok(_) => vec![],
-        Err(e) => {
-            // Map ASError to Diagnostic
-            let line = if e.location.line > 0 { e.location.line - 1 } else { 0 };
-            let col = if e.location.column > 0 { e.location.column - 1 } else { 0 };
-            
-            vec![serde_json::json!({
-                "range": {
-                    "start": { "line": line, "character": col },
-                    "end": { "line": line, "character": col + 1 }
-                },
-                "severity": 1, // Error
-                "message": e.message,
-                "source": "aslang"
-            })]
-        }
+/// The result for a request about an open document, null when there is
+/// nothing to report; `None` if the method isn't supported.
+fn handle_request(req: &Request, documents: &HashMap<String, String>) -> Option<Value> {
+    let handler: fn(&str, &str, &SourceLocation) -> Value = match req.method.as_str() {
+        "textDocument/hover" => |_, text, position| hover(text, position),
+        "textDocument/completion" => |_, text, position| completion(text, position),
+        "textDocument/definition" => definition,
+        "textDocument/documentSymbol" => |_, text, _| {
+            Value::Array(analysis::document_symbols(text).iter().map(document_symbol).collect())
+        },
+        _ => return None,
     };
+    let params = &req.params;
+    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+    let Some(text) = documents.get(uri) else {
+        return Some(Value::Null);
+    };
+    Some(handler(uri, text, &to_location(&params["position"])))
+}
+
+fn definition(uri: &str, text: &str, position: &SourceLocation) -> Value {
+    match analysis::definition_at(text, position) {
+        Some(span) => serde_json::json!({ "uri": uri, "range": to_range(&span) }),
+        None => Value::Null,
+    }
+}
+
+fn hover(text: &str, position: &SourceLocation) -> Value {
+    let (Some((_, span)), Some(ty)) = (analysis::identifier_at(text, position), analysis::type_at(text, position)) else {
+        return Value::Null;
+    };
+    serde_json::json!({
+        "contents": { "kind": "plaintext", "value": ty.to_string() },
+        "range": to_range(&span)
+    })
+}
+
+fn completion(text: &str, position: &SourceLocation) -> Value {
+    let items: Vec<Value> = analysis::completions(text, position).into_iter().map(|item| {
+        // LSP CompletionItemKind values
+        let kind = match item.kind {
+            CompletionKind::Keyword => 14,
+            CompletionKind::Variable => 6,
+            CompletionKind::Function => 3,
+        };
+        serde_json::json!({ "label": item.label, "kind": kind })
+    }).collect();
+    Value::Array(items)
+}
+
+fn document_symbol(symbol: &DocumentSymbol) -> Value {
+    // LSP SymbolKind values
+    let kind = match symbol.kind {
+        SymbolKind::Function => 12,
+        SymbolKind::Variable => 13,
+    };
+    serde_json::json!({
+        "name": symbol.name,
+        "kind": kind,
+        "range": to_range(&symbol.span),
+        "selectionRange": to_range(&symbol.span),
+        "children": symbol.children.iter().map(document_symbol).collect::<Vec<_>>()
+    })
+}
+
+/// Converts an LSP position (0-based) to a source location (1-based).
+fn to_location(position: &Value) -> SourceLocation {
+    let field = |name: &str| position[name].as_u64().unwrap_or(0) as usize + 1;
+    SourceLocation::new(field("line"), field("character"))
+}
+
+fn to_range(span: &Span) -> Value {
+    let position = |location: &SourceLocation| serde_json::json!({
+        "line": location.line.saturating_sub(1),
+        "character": location.column.saturating_sub(1)
+    });
+    serde_json::json!({ "start": position(&span.start), "end": position(&span.end) })
+}
+
+fn validate_document(connection: &Connection, uri: &str, text: &str) {
+    let file = analysis::file_path(uri);
+    let diagnostics: Vec<Value> = analysis::diagnostics(text, file.as_deref()).into_iter().map(|e| {
+        // Map ASError to Diagnostic
+        let line = if e.location.line > 0 { e.location.line - 1 } else { 0 };
+        let col = if e.location.column > 0 { e.location.column - 1 } else { 0 };
+        
+        serde_json::json!({
+            "range": {
+                "start": { "line": line, "character": col },
+                "end": { "line": line, "character": col + 1 }
+            },
+            "severity": 1, // Error
+            "message": e.message,
+            "source": "aslang"
+        })
+    }).collect();
+    publish_diagnostics(connection, uri, diagnostics);
+}
 
+fn publish_diagnostics(connection: &Connection, uri: &str, diagnostics: Vec<Value>) {
     let params = serde_json::json!({
         "uri": uri,
         "diagnostics": diagnostics
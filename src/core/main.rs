@@ -1,8 +1,11 @@
 use std::env;
 use std::fs;
+use std::path::PathBuf;
 use std::process;
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
+use aslang::compiler::{serialize, Compiler, Program};
+use aslang::parser::Parser;
 use aslang::runtime::Runtime;
 
 mod lsp;
@@ -27,17 +30,24 @@ fn main() {
                     process::exit(1);
                 }
             } else {
-                run_file(&args[1], false);
+                run_file(&args[1], false, false);
             }
         }
         3 => {
             if args[1] == "--debug" {
-                run_file(&args[2], true);
+                run_file(&args[2], true, false);
+            } else if args[1] == "--coverage" {
+                run_file(&args[2], false, true);
+            } else if args[1] == "--disasm" {
+                disassemble_file(&args[2]);
+            } else if args[1] == "run" {
+                run_bytecode_file(&args[2]);
             } else {
-                println!("Usage: aslang [filename.as] or aslang --debug [filename.as]");
+                println!("Usage: aslang [filename.as] or aslang [--debug|--coverage|--disasm] [filename.as]");
                 process::exit(1);
             }
         }
+        5 if args[1] == "compile" && args[2] == "-o" => compile_to_file(&args[4], &args[3]),
         _ => {
             println!("Usage: aslang [filename.as]");
             process::exit(1);
@@ -51,33 +61,54 @@ fn start_repl() {
     
     let mut runtime = Runtime::new();
     let mut rl = DefaultEditor::new().unwrap();
-    if rl.load_history("history.txt").is_err() {
-        // No previous history
+    let history = history_path();
+    if let Some(path) = &history {
+        if rl.load_history(path).is_err() {
+            // No previous history
+        }
     }
 
+    // Lines of a statement still being typed
+    let mut buffer = String::new();
     loop {
-        let readline = rl.readline("as > ");
+        let prompt = if buffer.is_empty() { "as > " } else { "... " };
+        let readline = rl.readline(prompt);
         match readline {
             Ok(line) => {
                 let input = line.trim();
                 let _ = rl.add_history_entry(line.as_str()); // Add raw line to history
                 
-                if input == "exit" {
-                    break;
+                if buffer.is_empty() {
+                    if input == "exit" {
+                        break;
+                    }
+                    if input.is_empty() {
+                        continue;
+                    }
                 }
-                if input.is_empty() {
+                
+                buffer.push_str(&line);
+                buffer.push('\n');
+                if Parser::is_incomplete(&buffer) {
                     continue;
                 }
+                let input = std::mem::take(&mut buffer);
                 
-                match runtime.execute(input) {
+                match runtime.execute(input.trim()) {
+                    // The output was already written to stdout as it ran
                     Ok(result) => {
-                        if !result.is_empty() {
-                           println!("{}", result);
+                        // Start the next prompt on its own line
+                        if !result.is_empty() && !result.ends_with('\n') {
+                            println!();
                         }
                     },
                     Err(e) => eprintln!("Error: {}", e),
                 }
             },
+            // Ctrl-C abandons a statement in progress
+            Err(ReadlineError::Interrupted) if !buffer.is_empty() => {
+                buffer.clear();
+            },
             Err(ReadlineError::Interrupted) => {
                 println!("CTRL-C");
                 break;
@@ -93,15 +124,35 @@ fn start_repl() {
         }
     }
     // Ignore error if saving history fails
-    let _ = rl.save_history("history.txt");
+    if let Some(path) = &history {
+        let _ = rl.save_history(path);
+    }
+}
+
+/// Where the REPL keeps its history: `.aslang_history` in the user's home
+/// directory, so running the REPL never leaves files in the current one.
+fn history_path() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(|home| PathBuf::from(home).join(".aslang_history"))
 }
 
-fn run_file(filename: &str, debug: bool) {
+fn run_file(filename: &str, debug: bool, coverage: bool) {
     match fs::read_to_string(filename) {
         Ok(contents) => {
             let mut runtime = Runtime::new();
             runtime.debug = debug;
-            match runtime.execute(&contents) {
+            runtime.coverage = coverage;
+            runtime.current_file = Some(PathBuf::from(filename));
+            let result = runtime.execute(&contents);
+            for warning in runtime.warnings() {
+                eprintln!("{}", warning);
+            }
+            if coverage {
+                print_coverage(&runtime);
+            }
+            match result {
                 Ok(_) => (),
                 Err(e) => {
                     eprintln!("Runtime error: {}", e);
@@ -114,4 +165,63 @@ fn run_file(filename: &str, debug: bool) {
             process::exit(1);
         }
     }
-}
\ No newline at end of file
+}
+
+/// Parses and compiles `filename`, exiting with a message on failure.
+fn compile_file(filename: &str) -> Program {
+    let source = fs::read_to_string(filename).unwrap_or_else(|e| {
+        eprintln!("Could not read file '{}': {}", filename, e);
+        process::exit(1);
+    });
+    Parser::parse(&source)
+        .and_then(|ast| Compiler::new().compile(&ast))
+        .unwrap_or_else(|e| {
+            eprintln!("Compile error: {}", e);
+            process::exit(1);
+        })
+}
+
+/// Prints the bytecode of the program and each of its functions.
+fn disassemble_file(filename: &str) {
+    let program = compile_file(filename);
+    for code in std::iter::once(&program.main).chain(&program.functions) {
+        println!("== {} ==", code.name);
+        print!("{}", Compiler::disassemble(&code.bytecode));
+    }
+}
+
+/// `aslang compile -o out.asc file.as`: saves the compiled program.
+fn compile_to_file(filename: &str, output: &str) {
+    let program = compile_file(filename);
+    if let Err(e) = fs::write(output, serialize::serialize_program(&program)) {
+        eprintln!("Could not write file '{}': {}", output, e);
+        process::exit(1);
+    }
+}
+
+/// `aslang run out.asc`: executes a program saved by `compile`.
+fn run_bytecode_file(filename: &str) {
+    let bytes = fs::read(filename).unwrap_or_else(|e| {
+        eprintln!("Could not read file '{}': {}", filename, e);
+        process::exit(1);
+    });
+    let result = serialize::deserialize_program(&bytes)
+        .and_then(|program| Runtime::new().execute_program(program));
+    if let Err(e) = result {
+        eprintln!("Runtime error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn print_coverage(runtime: &Runtime) {
+    let total = runtime.coverable_lines().len();
+    let covered = runtime.coverable_lines().intersection(runtime.covered_lines()).count();
+    let percent = if total == 0 { 100.0 } else { covered as f64 * 100.0 / total as f64 };
+    eprintln!("Coverage: {}/{} lines ({:.1}%)", covered, total, percent);
+    let uncovered: Vec<String> = runtime.coverable_lines().difference(runtime.covered_lines())
+        .map(|line| line.to_string())
+        .collect();
+    if !uncovered.is_empty() {
+        eprintln!("Uncovered lines: {}", uncovered.join(", "));
+    }
+}
@@ -1,24 +1,26 @@
 // Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
 
-use crate::lexer::{Lexer, Token};
-use crate::error::{ASError, ErrorKind, SourceLocation};
+use crate::lexer::{Lexer, StringPart, Token};
+use crate::error::{ASError, ErrorKind, SourceLocation, Span};
+use serde::Serialize;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum BinaryOp {
     Add, Subtract, Multiply, Divide, Modulo, Power,
     Eq, Ne, Lt, Le, Gt, Ge,
     And, Or, BitwiseAnd, BitwiseOr, LeftShift, RightShift,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum UnaryOp {
     Negate, Not, BitwiseNot, Increment, Decrement,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum Expression {
     Number(f64),
     String(String),
+    Char(char),
     Boolean(bool),
     Identifier(String),
     Call {
@@ -28,6 +30,10 @@ pub enum Expression {
     Array {
         elements: Vec<Expression>,
     },
+    /// `{"key": value, ...}`, with entries in source order.
+    Map {
+        entries: Vec<(String, Expression)>,
+    },
     Index {
         array: Box<Expression>,
         index: Box<Expression>,
@@ -42,16 +48,37 @@ pub enum Expression {
         operand: Box<Expression>,
     },
     Grouping(Box<Expression>),
+    /// `target = value`; evaluates to the assigned value.
+    Assign {
+        target: String,
+        value: Box<Expression>,
+    },
+    /// `target[index] = value`; only valid as a statement of its own.
+    IndexAssign {
+        target: String,
+        index: Box<Expression>,
+        value: Box<Expression>,
+    },
+}
+
+/// A node paired with the source range it was parsed from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// A sequence of statements, such as a program or a braced body.
+pub type Block = Vec<Spanned<Statement>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum Statement {
     Let {
         name: String,
         value: Expression,
         type_annotation: Option<crate::types::Type>,
     },
-    Output(Expression),
+    Output(Vec<Expression>),
     Input {
         prompt: Option<Expression>,
         target: String,
@@ -59,39 +86,52 @@ pub enum Statement {
     Function {
         name: String,
         params: Vec<String>,
-        body: Vec<Statement>,
+        /// Annotated type of each parameter, in parallel with `params`.
+        param_types: Vec<Option<crate::types::Type>>,
+        body: Block,
         return_type: Option<crate::types::Type>,
     },
     If {
         condition: Expression,
-        then_branch: Vec<Statement>,
-        elif_branches: Vec<(Expression, Vec<Statement>)>,
-        else_branch: Option<Vec<Statement>>,
+        then_branch: Block,
+        elif_branches: Vec<(Expression, Block)>,
+        else_branch: Option<Block>,
     },
     While {
         condition: Expression,
-        body: Vec<Statement>,
+        body: Block,
     },
     For {
         init: Option<Box<Statement>>,
         condition: Option<Expression>,
         update: Option<Box<Statement>>,
-        body: Vec<Statement>,
+        body: Block,
     },
     Break,
     Continue,
     Return(Option<Expression>),
     Import {
         path: String,
+        /// The names bound by `import { a, b } from "path"`; `None` for a
+        /// whole-file import.
+        names: Option<Vec<String>>,
     },
     ExpressionStmt(Expression),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct AST {
-    pub statements: Vec<Statement>,
+    pub statements: Block,
+}
+
+impl AST {
+    /// Source range of each top-level statement, in program order.
+    pub fn statement_spans(&self) -> Vec<Span> {
+        self.statements.iter().map(|stmt| stmt.span.clone()).collect()
+    }
 }
 
+#[allow(dead_code)]
 #[derive(PartialEq, PartialOrd)]
 enum Precedence {
     None,
@@ -102,6 +142,7 @@ enum Precedence {
     Comparison, // < > <= >=
     Term,       // + -
     Factor,     // * / %
+    Power,      // ^
     Unary,      // ! -
     Call,       // . () []
     Primary,
@@ -122,7 +163,7 @@ pub struct Parser<'a> {
 impl<'a> Parser<'a> {
     pub fn new(input: &'a str) -> Self {
         let mut lexer = Lexer::new(input);
-        let current_token = lexer.tokenize().unwrap_or(vec![Token::EOF]).first().cloned().unwrap_or(Token::EOF);
+        let _current_token = lexer.tokenize().unwrap_or(vec![Token::EOF]).first().cloned().unwrap_or(Token::EOF);
         // This is a bit simplified; real implementation should use iterator
         // For now, let's just reuse the simpler vector approach from before but adapted
         // Actually, let's rewrite to token iterator to be cleaner or just load all tokens
@@ -137,32 +178,136 @@ impl<'a> Parser<'a> {
     // Better approach: consume tokens from a vector
     pub fn parse(input: &'a str) -> Result<AST, ASError> {
         let mut lexer = Lexer::new(input);
-        let tokens = lexer.tokenize()?;
+        let tokens = lexer.tokenize_spanned()?;
         let mut parser = ParserInstance::new(tokens);
         parser.parse()
     }
+    
+    /// Parse `input`, carrying on past errors: a statement that fails to
+    /// parse is reported and skipped up to the next statement boundary. The
+    /// tree holds every statement that parsed; tokens after a lexer error are
+    /// not parsed. Use `parse` when only success matters.
+    pub fn parse_recovering(input: &'a str) -> (AST, Vec<ASError>) {
+        let (mut tokens, lex_error) = Lexer::new(input).tokenize_partial();
+        let mut errors = Vec::new();
+        let truncated = lex_error.is_some();
+        if let Some(error) = lex_error {
+            let end = error.location.clone();
+            tokens.push((Token::EOF, Span::new(end.clone(), end)));
+            errors.push(error);
+        }
+        let mut parser = ParserInstance::new(tokens);
+        let ast = parser.parse_recovering(&mut errors, truncated);
+        // Lexer and parser errors in source order
+        errors.sort_by_key(|error| (error.location.line, error.location.column));
+        (ast, errors)
+    }
+    
+    /// Whether `input` stops partway through a statement, such as inside
+    /// braces or a string, or right after an operator, so that more lines
+    /// could complete it. Input that is wrong rather than unfinished is not
+    /// incomplete.
+    pub fn is_incomplete(input: &'a str) -> bool {
+        let (tokens, error) = Lexer::new(input).tokenize_partial();
+        if let Some(error) = error {
+            // Strings and block comments may span lines
+            return matches!(error.message.as_str(),
+                "Unterminated string literal" | "Unterminated string interpolation" | "Unterminated block comment");
+        }
+        let mut parser = ParserInstance::new(tokens);
+        parser.parse().is_err() && parser.exhausted
+    }
+    
+    /// Parse `input` without type checking or compiling it, returning the
+    /// source range of each top-level statement.
+    pub fn statement_spans(input: &'a str) -> Result<Vec<Span>, ASError> {
+        Ok(Self::parse(input)?.statement_spans())
+    }
 }
 
 struct ParserInstance {
     tokens: Vec<Token>,
+    spans: Vec<Span>,
     current: usize,
+    /// Whether a token past the end of the input was asked for.
+    exhausted: bool,
 }
 
 impl ParserInstance {
-    fn new(tokens: Vec<Token>) -> Self {
+    fn new(tokens: Vec<(Token, Span)>) -> Self {
+        let (tokens, spans) = tokens.into_iter().unzip();
         ParserInstance {
             tokens,
+            spans,
             current: 0,
+            exhausted: false,
         }
     }
 
     fn parse(&mut self) -> Result<AST, ASError> {
         let mut statements = Vec::new();
         while !self.is_at_end() {
-            statements.push(self.parse_statement()?);
+            statements.push(self.parse_spanned_statement()?);
         }
         Ok(AST { statements })
     }
+    
+    /// `truncated` input stops at a lexer error, so a statement running into
+    /// its end is cut short rather than wrong and isn't reported.
+    fn parse_recovering(&mut self, errors: &mut Vec<ASError>, truncated: bool) -> AST {
+        let mut statements = Vec::new();
+        while !self.is_at_end() {
+            let start = self.current;
+            match self.parse_spanned_statement() {
+                Ok(statement) => statements.push(statement),
+                Err(_) if truncated && self.is_at_end() => break,
+                Err(error) => {
+                    errors.push(error);
+                    self.synchronize(start);
+                }
+            }
+        }
+        AST { statements }
+    }
+    
+    /// Skips the rest of a statement that failed to parse from token `start`:
+    /// up to the next `;` outside braces, the `}` closing the braces it
+    /// opened, or the keyword starting another statement.
+    fn synchronize(&mut self, start: usize) {
+        let mut depth: isize = self.tokens[start..self.current].iter().map(|token| match token {
+            Token::LBrace => 1,
+            Token::RBrace => -1,
+            _ => 0,
+        }).sum();
+        if self.current > start && depth <= 0 && matches!(self.previous(), Token::Semicolon | Token::RBrace) {
+            return;
+        }
+        while !self.is_at_end() {
+            match self.advance() {
+                Token::LBrace => depth += 1,
+                Token::RBrace => {
+                    depth -= 1;
+                    if depth <= 0 {
+                        return;
+                    }
+                }
+                Token::Semicolon if depth <= 0 => return,
+                _ => {}
+            }
+            let starts_statement = matches!(self.peek(), Token::Let | Token::Fn | Token::If | Token::While
+                | Token::For | Token::Output | Token::Input | Token::Return | Token::Import);
+            if depth <= 0 && starts_statement {
+                return;
+            }
+        }
+    }
+    
+    fn parse_spanned_statement(&mut self) -> Result<Spanned<Statement>, ASError> {
+        let start = self.span_at(self.current).start;
+        let node = self.parse_statement()?;
+        let end = self.span_at(self.current.saturating_sub(1)).end;
+        Ok(Spanned { node, span: Span::new(start, end) })
+    }
 
     fn parse_statement(&mut self) -> Result<Statement, ASError> {
         match self.peek() {
@@ -192,13 +337,39 @@ impl ParserInstance {
     fn parse_import(&mut self) -> Result<Statement, ASError> {
         self.advance(); // consume import
         
+        // Named form: import { a, b } from "path"
+        let names = if self.peek() == Token::LBrace {
+            self.advance();
+            let mut names = Vec::new();
+            loop {
+                match self.advance() {
+                    Token::Identifier(s) => names.push(s),
+                    _ => return Err(self.error("Expected name to import")),
+                }
+                if self.peek() == Token::Comma {
+                    self.advance();
+                } else {
+                    break;
+                }
+            }
+            if self.advance() != Token::RBrace {
+                return Err(self.error("Expected '}' after imported names"));
+            }
+            if self.advance() != Token::From {
+                return Err(self.error("Expected 'from' after imported names"));
+            }
+            Some(names)
+        } else {
+            None
+        };
+        
         let path = match self.advance() {
             Token::String(s) => s,
             _ => return Err(self.error("Expected string path after import")),
         };
         
         self.consume_semicolon()?;
-        Ok(Statement::Import { path })
+        Ok(Statement::Import { path, names })
     }
 
     fn parse_let(&mut self) -> Result<Statement, ASError> {
@@ -233,22 +404,50 @@ impl ParserInstance {
                 "Number" | "number" => Ok(crate::types::Type::Number),
                 "String" | "string" => Ok(crate::types::Type::String),
                 "Boolean" | "bool" => Ok(crate::types::Type::Boolean),
+                "Bytes" | "bytes" => Ok(crate::types::Type::Bytes),
+                "Char" | "char" => Ok(crate::types::Type::Char),
                 "Any" | "any" => Ok(crate::types::Type::Any),
                 "Void" | "void" => Ok(crate::types::Type::Void),
+                "Array" | "array" => Ok(crate::types::Type::Array(Box::new(self.parse_element_type()?))),
+                "Map" | "map" => Ok(crate::types::Type::Map(Box::new(self.parse_element_type()?))),
                 _ => Err(self.error(&format!("Unknown type: {}", s))),
             },
             _ => Err(self.error("Expected type name")),
         }
     }
 
+    /// The `<T>` of `Array<T>` or `Map<T>`.
+    fn parse_element_type(&mut self) -> Result<crate::types::Type, ASError> {
+        // A bare `Array` or `Map` leaves the element type open
+        if self.peek() != Token::Lt {
+            return Ok(crate::types::Type::Any);
+        }
+        self.advance(); // consume '<'
+        let element = self.parse_type()?;
+        match self.peek() {
+            Token::Gt => {
+                self.advance();
+            }
+            // `Array<Array<Number>>` lexes its closing pair as a shift; split it
+            Token::RightShift => self.tokens[self.current] = Token::Gt,
+            _ => return Err(self.error("Expected '>' after element type")),
+        }
+        Ok(element)
+    }
+
     fn parse_output(&mut self) -> Result<Statement, ASError> {
         self.advance(); // consume output
-        let expr = self.parse_expression(Precedence::None)?;
+        let mut exprs = vec![self.parse_expression(Precedence::None)?];
+        // output a, b, c prints the values space-separated on one line
+        while self.peek() == Token::Comma {
+            self.advance();
+            exprs.push(self.parse_expression(Precedence::None)?);
+        }
         // Output doesn't enforce semicolon in original design, but strict parser should
         if self.peek() == Token::Semicolon {
             self.advance();
         }
-        Ok(Statement::Output(expr))
+        Ok(Statement::Output(exprs))
     }
     
     fn parse_input(&mut self) -> Result<Statement, ASError> {
@@ -288,6 +487,7 @@ impl ParserInstance {
         }
         
         let mut params = Vec::new();
+        let mut param_types = Vec::new();
         if self.peek() != Token::RParen {
             loop {
                 match self.advance() {
@@ -295,6 +495,14 @@ impl ParserInstance {
                     _ => return Err(self.error("Expected parameter name")),
                 }
                 
+                // Optional type annotation: fn f(x: Type)
+                if self.peek() == Token::Colon {
+                    self.advance();
+                    param_types.push(Some(self.parse_type()?));
+                } else {
+                    param_types.push(None);
+                }
+                
                 if self.peek() == Token::Comma {
                     self.advance();
                 } else {
@@ -307,42 +515,41 @@ impl ParserInstance {
             return Err(self.error("Expected ')' after parameters"));
         }
         
+        // Optional return type: fn f() -> Type
+        let return_type = if self.peek() == Token::Arrow {
+            self.advance();
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        
         if self.advance() != Token::LBrace {
             return Err(self.error("Expected '{' before function body"));
         }
         
         let body = self.parse_block()?;
         
-        Ok(Statement::Function { name, params, body, return_type: None })
+        Ok(Statement::Function { name, params, param_types, body, return_type })
     }
     
     fn parse_if(&mut self) -> Result<Statement, ASError> {
         self.advance(); // consume if
         let condition = self.parse_expression(Precedence::None)?;
-        
-        if self.advance() != Token::LBrace {
-            return Err(self.error("Expected '{' after if condition"));
-        }
-        
-        let then_branch = self.parse_block()?;
+        let then_branch = self.parse_branch()?;
         let mut elif_branches = Vec::new();
         let mut else_branch = None;
         
+        // A braceless nested `if` consumes any following `elseif`/`else`
+        // itself, so they bind to the nearest `if`.
         while self.peek() == Token::ElseIf {
             self.advance();
             let elif_cond = self.parse_expression(Precedence::None)?;
-            if self.advance() != Token::LBrace {
-                return Err(self.error("Expected '{' after elseif condition"));
-            }
-            elif_branches.push((elif_cond, self.parse_block()?));
+            elif_branches.push((elif_cond, self.parse_branch()?));
         }
         
         if self.peek() == Token::Else {
             self.advance();
-            if self.advance() != Token::LBrace {
-                return Err(self.error("Expected '{' after else"));
-            }
-            else_branch = Some(self.parse_block()?);
+            else_branch = Some(self.parse_branch()?);
         }
         
         Ok(Statement::If { condition, then_branch, elif_branches, else_branch })
@@ -415,10 +622,21 @@ impl ParserInstance {
         Ok(Statement::Return(value))
     }
     
-    fn parse_block(&mut self) -> Result<Vec<Statement>, ASError> {
+    /// The body of an `if`/`elseif`/`else` branch: a braced block, or a
+    /// single statement when no `{` follows.
+    fn parse_branch(&mut self) -> Result<Block, ASError> {
+        if self.peek() == Token::LBrace {
+            self.advance();
+            self.parse_block()
+        } else {
+            Ok(vec![self.parse_spanned_statement()?])
+        }
+    }
+    
+    fn parse_block(&mut self) -> Result<Block, ASError> {
         let mut statements = Vec::new();
         while self.peek() != Token::RBrace && !self.is_at_end() {
-            statements.push(self.parse_statement()?);
+            statements.push(self.parse_spanned_statement()?);
         }
         if self.advance() != Token::RBrace {
             return Err(self.error("Expected '}'"));
@@ -442,11 +660,41 @@ impl ParserInstance {
         Ok(left)
     }
 
+    /// Lowers `"a ${x} b"` to `"a " + str(x) + " b"`.
+    fn parse_interpolation(&mut self, parts: Vec<StringPart>) -> Result<Expression, ASError> {
+        let mut result: Option<Expression> = None;
+        for part in parts {
+            let piece = match part {
+                StringPart::Literal(s) if s.is_empty() => continue,
+                StringPart::Literal(s) => Expression::String(s),
+                StringPart::Expression(tokens) => {
+                    let mut inner = ParserInstance::new(tokens);
+                    let expr = inner.parse_expression(Precedence::None)?;
+                    if !inner.is_at_end() {
+                        inner.advance();
+                        return Err(inner.error("Unexpected token in string interpolation"));
+                    }
+                    Expression::Call {
+                        function: Box::new(Expression::Identifier("str".to_string())),
+                        arguments: vec![expr],
+                    }
+                }
+            };
+            result = Some(match result {
+                Some(left) => Expression::BinaryOp { left: Box::new(left), operator: BinaryOp::Add, right: Box::new(piece) },
+                None => piece,
+            });
+        }
+        Ok(result.unwrap_or_else(|| Expression::String(String::new())))
+    }
+    
     fn parse_prefix(&mut self) -> Result<Expression, ASError> {
         let token = self.advance();
         match token {
             Token::Number(n) => Ok(Expression::Number(n)),
             Token::String(s) => Ok(Expression::String(s)),
+            Token::InterpolatedString(parts) => self.parse_interpolation(parts),
+            Token::Char(c) => Ok(Expression::Char(c)),
             Token::Boolean(b) => Ok(Expression::Boolean(b)),
             Token::Identifier(s) => Ok(Expression::Identifier(s)),
             Token::LParen => {
@@ -457,14 +705,18 @@ impl ParserInstance {
                 Ok(Expression::Grouping(Box::new(expr)))
             }
             Token::LBracket => self.parse_array(),
+            // Statements never start an expression, so a `{` here is a map
+            Token::LBrace => self.parse_map(),
             Token::Minus => self.parse_unary(UnaryOp::Negate),
             Token::Not => self.parse_unary(UnaryOp::Not),
+            Token::BitwiseNot => self.parse_unary(UnaryOp::BitwiseNot),
             _ => Err(self.error(&format!("Expected expression, found {:?}", token))),
         }
     }
     
     fn parse_unary(&mut self, op: UnaryOp) -> Result<Expression, ASError> {
-        let operand = self.parse_expression(Precedence::Unary)?;
+        // The operand takes in a following `^`, so `-2 ^ 2` is `-(2 ^ 2)`
+        let operand = self.parse_expression(Precedence::Factor)?;
         Ok(Expression::UnaryOp { operator: op, operand: Box::new(operand) })
     }
     
@@ -488,6 +740,32 @@ impl ParserInstance {
         Ok(Expression::Array { elements })
     }
 
+    fn parse_map(&mut self) -> Result<Expression, ASError> {
+        let mut entries = Vec::new();
+        while self.peek() != Token::RBrace {
+            // Bare identifiers are accepted as keys, as in `{name: "Ada"}`
+            let key = match self.advance() {
+                Token::String(s) | Token::Identifier(s) => s,
+                _ => return Err(self.error("Expected string key in map literal")),
+            };
+            if self.advance() != Token::Colon {
+                return Err(self.error("Expected ':' after map key"));
+            }
+            entries.push((key, self.parse_expression(Precedence::None)?));
+            if self.peek() == Token::Comma {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        
+        if self.advance() != Token::RBrace {
+            return Err(self.error("Expected '}'"));
+        }
+        
+        Ok(Expression::Map { entries })
+    }
+
     fn parse_infix(&mut self, left: Expression) -> Result<Expression, ASError> {
         let token = self.advance();
         match token {
@@ -496,6 +774,11 @@ impl ParserInstance {
             Token::Star => self.binary(left, BinaryOp::Multiply),
             Token::Slash => self.binary(left, BinaryOp::Divide),
             Token::Percent => self.binary(left, BinaryOp::Modulo),
+            Token::Caret => {
+                // Right-associative: `2 ^ 3 ^ 2` is `2 ^ (3 ^ 2)`
+                let right = self.parse_expression(Precedence::Factor)?;
+                Ok(Expression::BinaryOp { left: Box::new(left), operator: BinaryOp::Power, right: Box::new(right) })
+            }
             Token::EqEq => self.binary(left, BinaryOp::Eq),
             Token::Ne => self.binary(left, BinaryOp::Ne),
             Token::Lt => self.binary(left, BinaryOp::Lt),
@@ -504,6 +787,11 @@ impl ParserInstance {
             Token::Ge => self.binary(left, BinaryOp::Ge),
             Token::And => self.binary(left, BinaryOp::And),
             Token::Or => self.binary(left, BinaryOp::Or),
+            Token::Eq => self.assign(left),
+            Token::PlusEq => self.compound_assign(left, BinaryOp::Add),
+            Token::MinusEq => self.compound_assign(left, BinaryOp::Subtract),
+            Token::StarEq => self.compound_assign(left, BinaryOp::Multiply),
+            Token::SlashEq => self.compound_assign(left, BinaryOp::Divide),
             Token::LParen => self.call(left),
             Token::LBracket => self.index(left),
             _ => Err(self.error("Unknown infix operator")),
@@ -511,11 +799,43 @@ impl ParserInstance {
     }
     
     fn binary(&mut self, left: Expression, op: BinaryOp) -> Result<Expression, ASError> {
-        let precedence = self.get_precedence(self.prev());
+        let precedence = self.get_precedence(self.previous());
         let right = self.parse_expression(precedence)?;
         Ok(Expression::BinaryOp { left: Box::new(left), operator: op, right: Box::new(right) })
     }
     
+    fn assign(&mut self, target: Expression) -> Result<Expression, ASError> {
+        // Right-associative, so `a = b = 1` assigns both
+        match target {
+            Expression::Identifier(target) => {
+                let value = self.parse_expression(Precedence::None)?;
+                Ok(Expression::Assign { target, value: Box::new(value) })
+            }
+            Expression::Index { array, index } => {
+                let Expression::Identifier(target) = *array else {
+                    return Err(self.error("Invalid assignment target"));
+                };
+                let value = self.parse_expression(Precedence::None)?;
+                Ok(Expression::IndexAssign { target, index, value: Box::new(value) })
+            }
+            _ => Err(self.error("Invalid assignment target")),
+        }
+    }
+
+    /// Desugars `x += y` to `x = x + y`.
+    fn compound_assign(&mut self, target: Expression, op: BinaryOp) -> Result<Expression, ASError> {
+        let Expression::Identifier(target) = target else {
+            return Err(self.error("Invalid assignment target"));
+        };
+        let right = self.parse_expression(Precedence::None)?;
+        let value = Expression::BinaryOp {
+            left: Box::new(Expression::Identifier(target.clone())),
+            operator: op,
+            right: Box::new(right),
+        };
+        Ok(Expression::Assign { target, value: Box::new(value) })
+    }
+
     fn call(&mut self, function: Expression) -> Result<Expression, ASError> {
         let mut arguments = Vec::new();
         if self.peek() != Token::RParen {
@@ -544,19 +864,14 @@ impl ParserInstance {
         Ok(Expression::Index { array: Box::new(array), index: Box::new(index) })
     }
     
-    fn prev(&self) -> Token {
-        // Limitation of our simple vector parser, but tokens vec is available 
-        // Logic should be cleaner in real iter implementation
-        // For now hack:
-        self.tokens[self.current - 1].clone()
-    }
-
     fn get_precedence(&self, token: Token) -> Precedence {
         match token {
-            Token::Eq | Token::EqEq | Token::Ne => Precedence::Equality,
+            Token::Eq | Token::PlusEq | Token::MinusEq | Token::StarEq | Token::SlashEq => Precedence::Assignment,
+            Token::EqEq | Token::Ne => Precedence::Equality,
             Token::Lt | Token::Le | Token::Gt | Token::Ge => Precedence::Comparison,
             Token::Plus | Token::Minus => Precedence::Term,
             Token::Star | Token::Slash | Token::Percent => Precedence::Factor,
+            Token::Caret => Precedence::Power,
             Token::And => Precedence::And,
             Token::Or => Precedence::Or,
             Token::LParen => Precedence::Call,
@@ -566,14 +881,18 @@ impl ParserInstance {
     }
 
     fn advance(&mut self) -> Token {
-        if !self.is_at_end() {
+        if self.is_at_end() {
+            self.exhausted = true;
+        } else {
             self.current += 1;
         }
         self.previous()
     }
     
     fn previous(&self) -> Token {
-        self.tokens[self.current - 1].clone()
+        // Before anything is consumed there is no previous token; fall back to
+        // the first one (always at least EOF) rather than underflowing
+        self.tokens[self.current.saturating_sub(1)].clone()
     }
 
     fn peek(&self) -> Token {
@@ -584,10 +903,7 @@ impl ParserInstance {
     }
 
     fn is_at_end(&self) -> bool {
-        match self.peek() {
-            Token::EOF => true,
-            _ => false,
-        }
+        matches!(self.peek(), Token::EOF)
     }
     
     fn consume_semicolon(&mut self) -> Result<(), ASError> {
@@ -604,11 +920,19 @@ impl ParserInstance {
         }
     }
     
+    fn span_at(&self, index: usize) -> Span {
+        self.spans.get(index)
+            .or_else(|| self.spans.last())
+            .cloned()
+            .unwrap_or_else(|| Span::new(SourceLocation::new(0, 0), SourceLocation::new(0, 0)))
+    }
+    
     fn error(&self, message: &str) -> ASError {
+        // Errors are raised right after the offending token has been consumed
         ASError::new(
             ErrorKind::SyntaxError,
             message.to_string(),
-            SourceLocation::new(0, 0), // ToDo: propagating location from Token
+            self.span_at(self.current.saturating_sub(1)).start,
         )
     }
 }
\ No newline at end of file
@@ -2,44 +2,82 @@
 
 use std::path::{Path, PathBuf};
 use std::env;
+use std::ffi::OsStr;
 use std::io;
+use std::rc::Rc;
+use crate::error::ASError;
+
+/// Host callback that supplies the source of an imported module, or an error
+/// to deny the import. When installed it replaces filesystem resolution.
+pub type ImportHandler = Rc<dyn Fn(&str) -> Result<String, ASError>>;
 
 pub struct Resolver {
     root_dir: PathBuf,
+    /// Directories searched, in order, for relative imports not found next
+    /// to the importing file. Read from `ASLANG_PATH` by `new`.
+    pub search_paths: Vec<PathBuf>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Resolver {
     pub fn new() -> Self {
         Resolver {
             root_dir: env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            search_paths: env::var_os("ASLANG_PATH").map(|paths| Self::split_search_paths(&paths)).unwrap_or_default(),
         }
     }
 
+    /// The directories in an `ASLANG_PATH` value, which separates them as
+    /// `PATH` does on this platform. Empty entries are skipped.
+    pub fn split_search_paths(paths: &OsStr) -> Vec<PathBuf> {
+        env::split_paths(paths).filter(|path| !path.as_os_str().is_empty()).collect()
+    }
+
+    /// Finds the file an import names: relative to the importing file (or
+    /// the working directory), then in each search path. Each place may hold
+    /// the path as written, the path with `.as` appended, or a directory
+    /// with an `index.as`.
     pub fn resolve(&self, import_path: &str, current_file: Option<&Path>) -> io::Result<PathBuf> {
         let path = Path::new(import_path);
+        let base_dir = current_file.and_then(Path::parent).unwrap_or(&self.root_dir);
         
-        let target_path = if path.is_absolute() {
-            path.to_path_buf()
-        } else if let Some(current) = current_file {
-            if let Some(parent) = current.parent() {
-                parent.join(path)
-            } else {
-                self.root_dir.join(path)
-            }
-        } else {
-            self.root_dir.join(path)
-        };
-
-        // Canonicalize to resolve .. and . components if file exists
-        match target_path.canonicalize() {
-            Ok(p) => Ok(p),
-            Err(e) => {
-                // If file doesn't exist yet (not strictly required for lookup logic but good for runtime),
-                // we return error. For generic resolution we might want to just return the path.
-                // But for an import system, we generally want the file to exist.
-                Err(e)
+        // Absolute imports name a single place
+        let mut dirs = vec![base_dir];
+        if !path.is_absolute() {
+            dirs.extend(self.search_paths.iter().map(PathBuf::as_path));
+        }
+        
+        let mut tried = Vec::new();
+        for dir in dirs {
+            for candidate in Self::candidates(&dir.join(path)) {
+                if candidate.is_file() {
+                    // Canonicalize to resolve .. and . components
+                    return candidate.canonicalize();
+                }
+                tried.push(candidate.display().to_string());
             }
         }
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("module '{}' not found; tried {}", import_path, tried.join(", ")),
+        ))
+    }
+    
+    /// The files `target` may refer to, most specific first.
+    fn candidates(target: &Path) -> Vec<PathBuf> {
+        let mut candidates = vec![target.to_path_buf()];
+        if target.extension().is_none_or(|extension| extension != "as") {
+            let mut with_extension = target.as_os_str().to_owned();
+            with_extension.push(".as");
+            candidates.push(PathBuf::from(with_extension));
+        }
+        candidates.push(target.join("index.as"));
+        candidates
     }
     
     pub fn read_file(&self, path: &Path) -> io::Result<String> {
@@ -0,0 +1,502 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+use super::{Value, MAX_SAFE_INTEGER};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+/// Signature shared by every built-in function. Errors are plain messages;
+/// the runtime attaches the location of the failing call.
+pub type Builtin = fn(&[Value]) -> Result<Value, String>;
+
+/// Look up a built-in function by name.
+pub fn lookup(name: &str) -> Option<Builtin> {
+    match name {
+        "keys" => Some(keys),
+        "values" => Some(values),
+        "entries" => Some(entries),
+        "has_key" => Some(has_key),
+        "remove" => Some(remove),
+        "get" => Some(get),
+        "unique" => Some(unique),
+        "approx_eq" => Some(approx_eq),
+        "base64_encode" => Some(base64_encode),
+        "base64_decode" => Some(base64_decode),
+        "format" => Some(format),
+        "typeof" => Some(type_of),
+        "str" => Some(str),
+        "validate" => Some(validate),
+        "len" => Some(len),
+        "sqrt" => Some(sqrt),
+        "abs" => Some(abs),
+        "floor" => Some(floor),
+        "ceil" => Some(ceil),
+        "round" => Some(round),
+        "min" => Some(min),
+        "max" => Some(max),
+        "pow" => Some(pow),
+        "upper" => Some(upper),
+        "lower" => Some(lower),
+        "trim" => Some(trim),
+        "split" => Some(split),
+        "join" => Some(join),
+        "substring" => Some(substring),
+        "to_number" => Some(to_number),
+        "to_string" => Some(str),
+        _ => None,
+    }
+}
+
+fn expect_args(name: &str, args: &[Value], count: usize) -> Result<(), String> {
+    if args.len() != count {
+        return Err(format!("{}() expects {} argument(s), got {}", name, count, args.len()));
+    }
+    Ok(())
+}
+
+fn expect_map<'a>(name: &str, value: &'a Value) -> Result<&'a BTreeMap<String, Value>, String> {
+    match value {
+        Value::Map(entries) => Ok(entries),
+        other => Err(format!("{}() expects a map, got {}", name, other)),
+    }
+}
+
+fn expect_array<'a>(name: &str, value: &'a Value) -> Result<&'a [Value], String> {
+    match value {
+        Value::Array(elements) => Ok(elements),
+        other => Err(format!("{}() expects an array, got {}", name, other)),
+    }
+}
+
+fn expect_number(name: &str, value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Integer(n) => Ok(*n as f64),
+        Value::Number(n) => Ok(*n),
+        other => Err(format!("{}() expects a number, got {}", name, type_name(other))),
+    }
+}
+
+fn expect_string<'a>(name: &str, value: &'a Value) -> Result<&'a str, String> {
+    match value {
+        Value::String(s) => Ok(s),
+        other => Err(format!("{}() expects a string, got {}", name, type_name(other))),
+    }
+}
+
+fn expect_key<'a>(name: &str, value: &'a Value) -> Result<&'a str, String> {
+    match value {
+        Value::String(key) => Ok(key),
+        other => Err(format!("{}() expects a string key, got {}", name, other)),
+    }
+}
+
+/// `keys(m)`: the map's keys in sorted order.
+fn keys(args: &[Value]) -> Result<Value, String> {
+    expect_args("keys", args, 1)?;
+    let map = expect_map("keys", &args[0])?;
+    Ok(Value::Array(map.keys().map(|k| Value::String(k.clone())).collect()))
+}
+
+/// `values(m)`: the map's values, ordered by key.
+fn values(args: &[Value]) -> Result<Value, String> {
+    expect_args("values", args, 1)?;
+    let map = expect_map("values", &args[0])?;
+    Ok(Value::Array(map.values().cloned().collect()))
+}
+
+/// `entries(m)`: `[key, value]` pairs, ordered by key.
+fn entries(args: &[Value]) -> Result<Value, String> {
+    expect_args("entries", args, 1)?;
+    let map = expect_map("entries", &args[0])?;
+    Ok(Value::Array(map.iter()
+        .map(|(k, v)| Value::Array(vec![Value::String(k.clone()), v.clone()]))
+        .collect()))
+}
+
+/// `has_key(m, k)`: whether the map contains `k`.
+fn has_key(args: &[Value]) -> Result<Value, String> {
+    expect_args("has_key", args, 2)?;
+    let map = expect_map("has_key", &args[0])?;
+    let key = expect_key("has_key", &args[1])?;
+    Ok(Value::Boolean(map.contains_key(key)))
+}
+
+/// `remove(m, k)`: a copy of the map without `k`; a missing key is a no-op.
+fn remove(args: &[Value]) -> Result<Value, String> {
+    expect_args("remove", args, 2)?;
+    let mut map = expect_map("remove", &args[0])?.clone();
+    let key = expect_key("remove", &args[1])?;
+    map.remove(key);
+    Ok(Value::Map(map))
+}
+
+/// `get(m, k, default)`: the value stored at `k`, or `default` when absent.
+fn get(args: &[Value]) -> Result<Value, String> {
+    expect_args("get", args, 3)?;
+    let map = expect_map("get", &args[0])?;
+    let key = expect_key("get", &args[1])?;
+    Ok(map.get(key).cloned().unwrap_or_else(|| args[2].clone()))
+}
+
+/// `unique(arr)`: the array without duplicates, keeping first occurrences.
+/// Values are bucketed by hash and then compared for equality, so values
+/// that never compare equal (such as NaN) are all kept.
+fn unique(args: &[Value]) -> Result<Value, String> {
+    expect_args("unique", args, 1)?;
+    let elements = expect_array("unique", &args[0])?;
+    let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut result: Vec<Value> = Vec::new();
+    for element in elements {
+        let mut hasher = DefaultHasher::new();
+        element.hash(&mut hasher);
+        let bucket = buckets.entry(hasher.finish()).or_default();
+        if !bucket.iter().any(|&i| result[i] == *element) {
+            bucket.push(result.len());
+            result.push(element.clone());
+        }
+    }
+    Ok(Value::Array(result))
+}
+
+/// Whether `a` and `b` differ by at most `epsilon`. Equal values (including
+/// matching infinities) always compare equal.
+pub fn approx_equal(a: f64, b: f64, epsilon: f64) -> bool {
+    a == b || (a - b).abs() <= epsilon
+}
+
+/// `approx_eq(a, b, eps)`: whether two numbers are within `eps` of each other,
+/// independent of the runtime's `float_epsilon` setting.
+fn approx_eq(args: &[Value]) -> Result<Value, String> {
+    expect_args("approx_eq", args, 3)?;
+    let a = expect_number("approx_eq", &args[0])?;
+    let b = expect_number("approx_eq", &args[1])?;
+    let epsilon = expect_number("approx_eq", &args[2])?;
+    Ok(Value::Boolean(approx_equal(a, b, epsilon)))
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// `base64_encode(data)`: standard, padded base64 of a bytes value or of a
+/// string's UTF-8 encoding.
+fn base64_encode(args: &[Value]) -> Result<Value, String> {
+    expect_args("base64_encode", args, 1)?;
+    let data = match &args[0] {
+        Value::Bytes(bytes) => bytes.as_slice(),
+        Value::String(s) => s.as_bytes(),
+        other => return Err(format!("base64_encode() expects bytes or a string, got {}", other)),
+    };
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    Ok(Value::String(encoded))
+}
+
+/// `base64_decode(s)`: the bytes encoded by a standard base64 string.
+/// Padding is optional; whitespace is ignored.
+fn base64_decode(args: &[Value]) -> Result<Value, String> {
+    expect_args("base64_decode", args, 1)?;
+    let text = match &args[0] {
+        Value::String(s) => s,
+        other => return Err(format!("base64_decode() expects a string, got {}", other)),
+    };
+    let digits: Vec<u8> = text.bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+    let digits = digits.strip_suffix(b"==").or_else(|| digits.strip_suffix(b"=")).unwrap_or(&digits);
+    if digits.len() % 4 == 1 {
+        return Err("base64_decode() got input of invalid length".to_string());
+    }
+    let mut decoded = Vec::with_capacity(digits.len() * 3 / 4);
+    for chunk in digits.chunks(4) {
+        let mut group = 0u32;
+        for (i, &digit) in chunk.iter().enumerate() {
+            let value = BASE64_ALPHABET.iter().position(|&c| c == digit)
+                .ok_or_else(|| format!("base64_decode() got invalid character '{}'", digit as char))?;
+            group |= (value as u32) << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            decoded.push((group >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(Value::Bytes(decoded))
+}
+
+/// `format(template, ...args)`: substitutes each `{}` in the template with the
+/// next argument as `output` would print it; `{:.N}` prints a number with N
+/// decimal places. `{{` and `}}` produce literal braces.
+fn format(args: &[Value]) -> Result<Value, String> {
+    let template = match args.first() {
+        Some(Value::String(s)) => s,
+        Some(other) => return Err(format!("format() expects a string template, got {}", other)),
+        None => return Err("format() expects a template".to_string()),
+    };
+    let mut values = args[1..].iter();
+    let mut result = String::new();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.as_str().starts_with('{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.as_str().starts_with('}') => {
+                chars.next();
+                result.push('}');
+            }
+            '{' => {
+                let rest = chars.as_str();
+                let end = rest.find('}').ok_or("format() template has an unclosed '{'")?;
+                let spec = &rest[..end];
+                chars = rest[end + 1..].chars();
+                let value = values.next()
+                    .ok_or_else(|| format!("format() has more placeholders than arguments ({} given)", args.len() - 1))?;
+                match spec {
+                    "" => result.push_str(&value.to_string()),
+                    _ => {
+                        let precision = spec.strip_prefix(":.")
+                            .and_then(|digits| digits.parse::<usize>().ok())
+                            .ok_or_else(|| format!("format() got unknown placeholder '{{{}}}'", spec))?;
+                        match value {
+                            Value::Integer(n) => result.push_str(&format!("{:.*}", precision, *n as f64)),
+                            Value::Number(n) => result.push_str(&format!("{:.*}", precision, n)),
+                            other => return Err(format!("format() precision requires a number, got {}", other)),
+                        }
+                    }
+                }
+            }
+            '}' => return Err("format() template has an unmatched '}'".to_string()),
+            _ => result.push(c),
+        }
+    }
+    if values.next().is_some() {
+        return Err(format!("format() has more arguments ({}) than placeholders", args.len() - 1));
+    }
+    Ok(Value::String(result))
+}
+
+/// Name of a value's type as reported by `typeof`.
+pub(crate) fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Integer(_) | Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Char(_) => "char",
+        Value::Boolean(_) => "boolean",
+        Value::Array(_) => "array",
+        Value::Map(_) => "map",
+        Value::Bytes(_) => "bytes",
+        Value::Function(_) => "function",
+        Value::None => "none",
+    }
+}
+
+/// `typeof(v)`: the value's type name, e.g. `"number"` or `"map"`.
+fn type_of(args: &[Value]) -> Result<Value, String> {
+    expect_args("typeof", args, 1)?;
+    Ok(Value::String(type_name(&args[0]).to_string()))
+}
+
+/// `validate(value, schema)`: checks `value` against `schema` and returns a
+/// list of problems, empty when it conforms. A schema is either a type name
+/// as returned by `typeof` (or `"any"`), or a map from field names to
+/// schemas, which requires a map value with those fields and recurses.
+fn validate(args: &[Value]) -> Result<Value, String> {
+    expect_args("validate", args, 2)?;
+    let mut errors = Vec::new();
+    validate_against(&args[0], &args[1], "", &mut errors)?;
+    Ok(Value::Array(errors.into_iter().map(Value::String).collect()))
+}
+
+fn validate_against(value: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) -> Result<(), String> {
+    let at = if path.is_empty() { "value" } else { path };
+    match schema {
+        Value::String(expected) => {
+            let actual = type_name(value);
+            if expected != "any" && expected != actual {
+                errors.push(format!("{}: expected {}, got {}", at, expected, actual));
+            }
+        }
+        Value::Map(fields) => {
+            let Value::Map(entries) = value else {
+                errors.push(format!("{}: expected map, got {}", at, type_name(value)));
+                return Ok(());
+            };
+            for (field, field_schema) in fields {
+                let field_path = if path.is_empty() { field.clone() } else { format!("{}.{}", path, field) };
+                match entries.get(field) {
+                    Some(field_value) => validate_against(field_value, field_schema, &field_path, errors)?,
+                    None => errors.push(format!("{}: missing field", field_path)),
+                }
+            }
+        }
+        other => return Err(format!("validate() schema must be a type name or a map, got {}", other)),
+    }
+    Ok(())
+}
+
+/// `str(v)` and `to_string(v)`: the value as `output` would print it.
+fn str(args: &[Value]) -> Result<Value, String> {
+    expect_args("str", args, 1)?;
+    Ok(Value::String(args[0].to_string()))
+}
+
+/// `len(x)`: the number of elements of an array, map or bytes value, or of
+/// characters in a string.
+fn len(args: &[Value]) -> Result<Value, String> {
+    expect_args("len", args, 1)?;
+    let count = match &args[0] {
+        Value::Array(elements) => elements.len(),
+        Value::String(s) => s.chars().count(),
+        Value::Map(entries) => entries.len(),
+        Value::Bytes(bytes) => bytes.len(),
+        other => return Err(format!("len() expects a string, array, map or bytes, got {}", type_name(other))),
+    };
+    Ok(Value::Integer(count as i64))
+}
+
+/// A whole-number result as an `Integer` when it is exactly representable.
+fn whole(n: f64) -> Value {
+    if n.abs() <= MAX_SAFE_INTEGER {
+        Value::Integer(n as i64)
+    } else {
+        Value::Number(n)
+    }
+}
+
+/// `sqrt(x)`: the square root; negative input yields NaN.
+fn sqrt(args: &[Value]) -> Result<Value, String> {
+    expect_args("sqrt", args, 1)?;
+    Ok(Value::Number(expect_number("sqrt", &args[0])?.sqrt()))
+}
+
+/// `abs(x)`: the magnitude, keeping integers integral.
+fn abs(args: &[Value]) -> Result<Value, String> {
+    expect_args("abs", args, 1)?;
+    match &args[0] {
+        Value::Integer(n) => Ok(n.checked_abs().map_or(Value::Number((*n as f64).abs()), Value::Integer)),
+        other => Ok(Value::Number(expect_number("abs", other)?.abs())),
+    }
+}
+
+/// `floor(x)`: the largest whole number not above `x`.
+fn floor(args: &[Value]) -> Result<Value, String> {
+    expect_args("floor", args, 1)?;
+    Ok(whole(expect_number("floor", &args[0])?.floor()))
+}
+
+/// `ceil(x)`: the smallest whole number not below `x`.
+fn ceil(args: &[Value]) -> Result<Value, String> {
+    expect_args("ceil", args, 1)?;
+    Ok(whole(expect_number("ceil", &args[0])?.ceil()))
+}
+
+/// `round(x)`: the nearest whole number, rounding halves away from zero.
+fn round(args: &[Value]) -> Result<Value, String> {
+    expect_args("round", args, 1)?;
+    Ok(whole(expect_number("round", &args[0])?.round()))
+}
+
+/// `min(a, b)`: the smaller argument, returned unchanged.
+fn min(args: &[Value]) -> Result<Value, String> {
+    expect_args("min", args, 2)?;
+    let (a, b) = (expect_number("min", &args[0])?, expect_number("min", &args[1])?);
+    Ok(if b < a { args[1].clone() } else { args[0].clone() })
+}
+
+/// `max(a, b)`: the larger argument, returned unchanged.
+fn max(args: &[Value]) -> Result<Value, String> {
+    expect_args("max", args, 2)?;
+    let (a, b) = (expect_number("max", &args[0])?, expect_number("max", &args[1])?);
+    Ok(if b > a { args[1].clone() } else { args[0].clone() })
+}
+
+/// `pow(base, exponent)`: `base` raised to `exponent`.
+fn pow(args: &[Value]) -> Result<Value, String> {
+    expect_args("pow", args, 2)?;
+    let (base, exponent) = (expect_number("pow", &args[0])?, expect_number("pow", &args[1])?);
+    Ok(Value::Number(base.powf(exponent)))
+}
+
+/// `upper(s)`: the string in upper case.
+fn upper(args: &[Value]) -> Result<Value, String> {
+    expect_args("upper", args, 1)?;
+    Ok(Value::String(expect_string("upper", &args[0])?.to_uppercase()))
+}
+
+/// `lower(s)`: the string in lower case.
+fn lower(args: &[Value]) -> Result<Value, String> {
+    expect_args("lower", args, 1)?;
+    Ok(Value::String(expect_string("lower", &args[0])?.to_lowercase()))
+}
+
+/// `trim(s)`: the string without leading and trailing whitespace.
+fn trim(args: &[Value]) -> Result<Value, String> {
+    expect_args("trim", args, 1)?;
+    Ok(Value::String(expect_string("trim", &args[0])?.trim().to_string()))
+}
+
+/// `split(s, sep)`: the pieces of `s` between occurrences of `sep`. An
+/// empty separator splits into single characters.
+fn split(args: &[Value]) -> Result<Value, String> {
+    expect_args("split", args, 2)?;
+    let s = expect_string("split", &args[0])?;
+    let separator = expect_string("split", &args[1])?;
+    let pieces: Vec<Value> = if separator.is_empty() {
+        s.chars().map(|c| Value::String(c.to_string())).collect()
+    } else {
+        s.split(separator).map(|piece| Value::String(piece.to_string())).collect()
+    };
+    Ok(Value::Array(pieces))
+}
+
+/// `join(arr, sep)`: the array's strings joined with `sep` between them.
+fn join(args: &[Value]) -> Result<Value, String> {
+    expect_args("join", args, 2)?;
+    let elements = expect_array("join", &args[0])?;
+    let separator = expect_string("join", &args[1])?;
+    let pieces = elements.iter()
+        .map(|element| match element {
+            Value::String(s) => Ok(s.as_str()),
+            other => Err(format!("join() expects an array of strings, found {}", type_name(other))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Value::String(pieces.join(separator)))
+}
+
+/// `substring(s, start, end)`: the characters from `start` up to, but not
+/// including, `end`.
+fn substring(args: &[Value]) -> Result<Value, String> {
+    expect_args("substring", args, 3)?;
+    let s = expect_string("substring", &args[0])?;
+    let length = s.chars().count();
+    let bound = |value: &Value| match value {
+        Value::Integer(n) if *n >= 0 && *n as usize <= length => Ok(*n as usize),
+        other => Err(format!("substring() bound {} is not an integer from 0 to {}", other, length)),
+    };
+    let (start, end) = (bound(&args[1])?, bound(&args[2])?);
+    if start > end {
+        return Err(format!("substring() start {} is after end {}", start, end));
+    }
+    Ok(Value::String(s.chars().skip(start).take(end - start).collect()))
+}
+
+/// `to_number(s)`: the string parsed as a number, surrounding whitespace
+/// ignored. Whole numbers become integers.
+fn to_number(args: &[Value]) -> Result<Value, String> {
+    expect_args("to_number", args, 1)?;
+    let s = expect_string("to_number", &args[0])?.trim();
+    if let Ok(n) = s.parse::<i64>() {
+        return Ok(Value::Integer(n));
+    }
+    match s.parse::<f64>() {
+        Ok(n) if n.is_finite() => Ok(Value::Number(n)),
+        _ => Err(format!("to_number() could not parse '{}' as a number", s)),
+    }
+}
@@ -1,25 +1,56 @@
 // Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
 
-use crate::compiler::{Compiler, Opcode};
-use crate::parser::Parser;
-use crate::types::TypeChecker;
-use crate::error::{ASError, ErrorKind, SourceLocation};
-use std::collections::HashMap;
+use crate::compiler::{CodeObject, Compiler, Constant, Opcode, Program};
+use crate::parser::{Block, Parser, Statement};
+use crate::types::{Type, TypeChecker};
+use crate::error::{ASError, ErrorKind, SourceLocation, Warning};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+pub mod builtins;
+
+/// Largest integer an f64 represents exactly (2^53 - 1).
+pub const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
+    /// A whole number from integer literals and integer-only arithmetic.
+    Integer(i64),
     Number(f64),
     String(String),
+    Char(char),
     Boolean(bool),
     Array(Vec<Value>),
+    /// String-keyed map; keys are kept in sorted order.
+    Map(BTreeMap<String, Value>),
+    /// Raw binary data; indexing yields each byte as a number.
+    Bytes(Vec<u8>),
+    Function(FunctionRef),
     None,
 }
 
+/// A function used as a value. Two references are equal only when they
+/// name the same definition, never because their bodies look alike.
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub struct FunctionRef {
+    /// Position of the definition in the program's function table.
+    pub index: usize,
+    /// `None` for anonymous functions.
+    pub name: Option<String>,
+    pub arity: usize,
+}
+
+/// Bytes beyond this many are elided when displaying a `Value::Bytes`.
+const BYTES_DISPLAY_LIMIT: usize = 16;
+
 impl std::fmt::Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
+            Value::Integer(n) => write!(f, "{}", n),
             Value::Number(n) => write!(f, "{}", n),
             Value::String(s) => write!(f, "{}", s),
+            Value::Char(c) => write!(f, "{}", c),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::Array(elements) => {
                 write!(f, "[")?;
@@ -31,20 +62,207 @@ impl std::fmt::Display for Value {
                 }
                 write!(f, "]")
             }
+            Value::Map(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+                write!(f, "}}")
+            }
+            Value::Bytes(bytes) => {
+                write!(f, "<bytes {}: ", bytes.len())?;
+                for byte in bytes.iter().take(BYTES_DISPLAY_LIMIT) {
+                    write!(f, "{:02x}", byte)?;
+                }
+                if bytes.len() > BYTES_DISPLAY_LIMIT {
+                    write!(f, "...")?;
+                }
+                write!(f, ">")
+            }
+            Value::Function(function) => {
+                let name = function.name.as_deref().unwrap_or("anonymous");
+                write!(f, "<function {} arity={}>", name, function.arity)
+            }
             Value::None => write!(f, "none"),
         }
     }
 }
 
-use crate::resolver::Resolver;
+impl Value {
+    /// The static type the checker assigns to this value.
+    pub fn static_type(&self) -> Type {
+        match self {
+            Value::Integer(_) | Value::Number(_) => Type::Number,
+            Value::String(_) => Type::String,
+            Value::Char(_) => Type::Char,
+            Value::Boolean(_) => Type::Boolean,
+            Value::Array(elements) => {
+                let element = elements.first().map_or(Type::Any, Value::static_type);
+                Type::Array(Box::new(element))
+            }
+            Value::Map(entries) => {
+                let value = entries.values().next().map_or(Type::Any, Value::static_type);
+                Type::Map(Box::new(value))
+            }
+            Value::Bytes(_) => Type::Bytes,
+            Value::Function(_) | Value::None => Type::Any,
+        }
+    }
+
+    /// Approximate bytes used by this value, counting its own slot plus any
+    /// heap storage it owns, recursively.
+    pub fn estimated_size(&self) -> usize {
+        let heap = match self {
+            Value::String(s) => s.capacity(),
+            Value::Array(elements) => elements.iter().map(Value::estimated_size).sum(),
+            Value::Map(entries) => entries.iter()
+                .map(|(key, value)| std::mem::size_of::<String>() + key.capacity() + value.estimated_size())
+                .sum(),
+            Value::Bytes(bytes) => bytes.capacity(),
+            Value::Function(function) => function.name.as_ref().map_or(0, String::capacity),
+            Value::Integer(_) | Value::Number(_) | Value::Char(_) | Value::Boolean(_) | Value::None => 0,
+        };
+        std::mem::size_of::<Value>() + heap
+    }
+}
+
+impl std::hash::Hash for Value {
+    /// Consistent with `PartialEq`: values that compare equal hash equally.
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            // 0.0 and -0.0 compare equal, so they must hash the same
+            Value::Integer(n) => n.hash(state),
+            Value::Number(n) => (if *n == 0.0 { 0.0 } else { *n }).to_bits().hash(state),
+            Value::String(s) => s.hash(state),
+            Value::Char(c) => c.hash(state),
+            Value::Boolean(b) => b.hash(state),
+            Value::Array(elements) => elements.hash(state),
+            Value::Map(entries) => entries.hash(state),
+            Value::Bytes(bytes) => bytes.hash(state),
+            Value::Function(function) => function.hash(state),
+            Value::None => {}
+        }
+    }
+}
+
+/// One executed opcode, as recorded by the post-mortem trace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub pc: usize,
+    pub opcode: Opcode,
+    pub stack_depth: usize,
+    pub stack_top: Option<Value>,
+}
+
+impl std::fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:04} {:?} (stack: {}", self.pc, self.opcode, self.stack_depth)?;
+        if let Some(top) = &self.stack_top {
+            write!(f, ", top: {}", top)?;
+        }
+        write!(f, ")")
+    }
+}
+
+use crate::resolver::{ImportHandler, Resolver};
+
+/// Supplies lines for `input` statements in place of standard input. It is
+/// passed the statement's prompt, or "" when there is none.
+pub type InputHandler = Box<dyn FnMut(&str) -> Result<String, ASError>>;
+
+/// Receives program output in place of standard output.
+pub type OutputWriter = Box<dyn std::io::Write>;
+
+/// An active function call: the caller to resume when it returns.
+struct CallFrame {
+    caller: Rc<CodeObject>,
+    return_pc: usize,
+    /// The module whose globals the caller was using.
+    caller_module: Option<usize>,
+}
 
 pub struct Runtime {
     compiler: Compiler,
     type_checker: TypeChecker,
     resolver: Resolver,
     stack: Vec<Value>,
+    /// Global variables, by name.
     variables: HashMap<String, Value>,
+    /// Local slots of each running code object (the main program, an
+    /// import or a function call), innermost last.
+    locals: Vec<Vec<Value>>,
+    functions: Vec<Rc<CodeObject>>,
+    frames: Vec<CallFrame>,
+    /// The code object and PC of the opcode being executed, for diagnostics.
+    current: Option<(Rc<CodeObject>, usize)>,
+    location: SourceLocation,
+    trace: VecDeque<TraceEntry>,
+    trace_capacity: usize,
+    import_handler: Option<ImportHandler>,
+    /// Keys of the modules already imported, which later imports skip.
+    loaded_modules: HashSet<String>,
+    /// Globals of each module imported by name, indexed by module id.
+    module_globals: Vec<HashMap<String, Value>>,
+    /// Module id of each module imported by name, by module key.
+    named_modules: HashMap<String, usize>,
+    /// Module id of each function defined by a module imported by name;
+    /// such functions run with their module's globals.
+    function_modules: HashMap<usize, usize>,
+    /// The module whose globals `variables` holds; `None` for the program.
+    current_module: Option<usize>,
+    /// The program's globals while code of a module imported by name runs.
+    program_globals: HashMap<String, Value>,
+    input_handler: Option<InputHandler>,
+    output_writer: Option<OutputWriter>,
+    warnings: Vec<Warning>,
+    covered_lines: BTreeSet<usize>,
+    coverable_lines: BTreeSet<usize>,
+    /// Opcodes executed by the current `execute`.
+    instructions: u64,
     pub debug: bool,
+    /// Record which source lines execute, for `covered_lines`.
+    pub coverage: bool,
+    /// Load unknown variables as `none` instead of raising an error.
+    pub undefined_is_none: bool,
+    /// Raise an error when arithmetic leaves the range of integers an f64
+    /// represents exactly (±2^53 - 1), instead of silently losing precision.
+    pub strict_integers: bool,
+    /// Tolerance for `==` and `!=` between two numbers; `None` (the default)
+    /// compares exactly. Comparisons involving other types are unaffected.
+    pub float_epsilon: Option<f64>,
+    /// Abort with an error once `estimated_memory` exceeds this many bytes.
+    /// Checked after each opcode that can allocate (see `may_allocate`), at
+    /// a cost proportional to the amount of live data; `None` (the default)
+    /// disables the check.
+    pub memory_limit: Option<usize>,
+    /// Abort with an error after executing this many opcodes in one
+    /// `execute`, e.g. to stop runaway loops in untrusted code. `None` (the
+    /// default) never stops; see `set_instruction_limit`.
+    pub max_instructions: Option<u64>,
+    /// Most function calls that may be active at once; one more raises an
+    /// error instead of growing without bound. Defaults to 1,000; see
+    /// `set_max_call_depth`.
+    pub max_call_depth: usize,
+    /// Store `input` that parses as a finite number as a `Number` rather
+    /// than a `String`. Off by default.
+    pub numeric_input: bool,
+    /// Resolve top-level imports before compiling, merging each module into
+    /// the program once, instead of compiling and running a module every
+    /// time its `import` executes.
+    pub link_imports: bool,
+    /// Path of the file being executed, if any; its relative imports
+    /// resolve against its directory instead of the working directory.
+    pub current_file: Option<PathBuf>,
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Runtime {
@@ -55,39 +273,290 @@ impl Runtime {
             resolver: Resolver::new(),
             stack: Vec::new(),
             variables: HashMap::new(),
+            locals: Vec::new(),
+            functions: Vec::new(),
+            frames: Vec::new(),
+            current: None,
+            location: SourceLocation::new(0, 0),
+            trace: VecDeque::new(),
+            trace_capacity: 0,
+            import_handler: None,
+            loaded_modules: HashSet::new(),
+            module_globals: Vec::new(),
+            named_modules: HashMap::new(),
+            function_modules: HashMap::new(),
+            current_module: None,
+            program_globals: HashMap::new(),
+            input_handler: None,
+            output_writer: None,
+            warnings: Vec::new(),
+            covered_lines: BTreeSet::new(),
+            coverable_lines: BTreeSet::new(),
+            instructions: 0,
             debug: false,
+            coverage: false,
+            undefined_is_none: false,
+            strict_integers: false,
+            float_epsilon: None,
+            memory_limit: None,
+            max_instructions: None,
+            max_call_depth: 1000,
+            numeric_input: false,
+            link_imports: false,
+            current_file: None,
+        }
+    }
+
+    /// Keep the last `capacity` executed opcodes for post-mortem debugging.
+    /// A capacity of 0 (the default) disables tracing.
+    pub fn set_trace_capacity(&mut self, capacity: usize) {
+        self.trace_capacity = capacity;
+        while self.trace.len() > capacity {
+            self.trace.pop_front();
         }
     }
+    
+    /// The most recently executed opcodes of the last `execute`, oldest first.
+    /// After a runtime error, the final entry is the opcode that failed.
+    pub fn trace(&self) -> Vec<TraceEntry> {
+        self.trace.iter().cloned().collect()
+    }
+
+    /// Serve `import` statements from `handler` instead of the filesystem.
+    /// The handler receives the import path and returns the module source,
+    /// or an error to deny the import.
+    pub fn set_import_handler<F>(&mut self, handler: F)
+    where
+        F: Fn(&str) -> Result<String, ASError> + 'static,
+    {
+        let handler: ImportHandler = std::rc::Rc::new(handler);
+        self.type_checker.import_handler = Some(handler.clone());
+        self.import_handler = Some(handler);
+    }
+
+    /// Stop each `execute` with an "Instruction budget exceeded" error after
+    /// `limit` opcodes, or never with `None` (the default).
+    pub fn set_instruction_limit(&mut self, limit: Option<u64>) {
+        self.max_instructions = limit;
+    }
+
+    /// Allow at most `depth` active function calls; one more raises a
+    /// "Maximum call depth" error rather than exhausting the native stack.
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Approximate bytes held by live variables, local slots and the value
+    /// stack, including the heap storage of strings, arrays and maps.
+    pub fn estimated_memory(&self) -> usize {
+        let globals = std::iter::once(&self.variables).chain(&self.module_globals).chain(std::iter::once(&self.program_globals))
+            .flatten()
+            .map(|(name, value)| name.capacity() + value.estimated_size());
+        let values = self.locals.iter().flatten().chain(&self.stack);
+        globals.sum::<usize>() + values.map(Value::estimated_size).sum::<usize>()
+    }
+
+    /// Source lines that executed at least once during the last `execute`
+    /// with `coverage` enabled.
+    pub fn covered_lines(&self) -> &BTreeSet<usize> {
+        &self.covered_lines
+    }
+
+    /// Source lines of the last executed program that compiled to at least
+    /// one opcode; blank and comment-only lines are never included.
+    pub fn coverable_lines(&self) -> &BTreeSet<usize> {
+        &self.coverable_lines
+    }
+
+    /// Compiler warnings for the program passed to the last `execute`.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Bind a global variable before `execute`, so scripts can read `name`
+    /// without declaring it. The type checker sees it with the value's type.
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.type_checker.define_variable(name, value.static_type());
+        // A run that failed inside a named import's module may have left its globals in place
+        self.switch_globals(None);
+        self.variables.insert(name.to_string(), value);
+    }
+
+    /// Read `input` lines from `handler` instead of standard input, e.g. to
+    /// script a program's input in tests or when embedding.
+    pub fn set_input_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(&str) -> Result<String, ASError> + 'static,
+    {
+        self.input_handler = Some(Box::new(handler));
+    }
+
+    /// Go back to reading `input` lines from standard input.
+    pub fn clear_input_handler(&mut self) {
+        self.input_handler = None;
+    }
+
+    /// Write program output to `writer` instead of standard output. The
+    /// text is still collected into the string `execute` returns.
+    pub fn set_output_writer<W>(&mut self, writer: W)
+    where
+        W: std::io::Write + 'static,
+    {
+        self.output_writer = Some(Box::new(writer));
+    }
+
+    /// Appends `text` to the collected `output` and writes it to the
+    /// output writer, or standard output when none is set.
+    fn write_output(&mut self, output: &mut String, text: &str) -> Result<(), ASError> {
+        output.push_str(text);
+        let written = match &mut self.output_writer {
+            Some(writer) => writer.write_all(text.as_bytes()),
+            None => std::io::Write::write_all(&mut std::io::stdout(), text.as_bytes()),
+        };
+        written.map_err(|e| self.error(&format!("Failed to write output: {}", e)))
+    }
 
     pub fn execute(&mut self, input: &str) -> Result<String, ASError> {
-        let ast = Parser::parse(input)?;
+        self.trace.clear();
+        self.warnings.clear();
+        let mut ast = Parser::parse(input)?;
+        if self.link_imports {
+            ast.statements = self.link_imports(ast.statements, self.current_file.as_deref(), &mut HashSet::new())?;
+        }
         
         // Type check before compilation
+        self.type_checker.allow_undefined = self.undefined_is_none;
+        self.type_checker.current_file = self.current_file.clone();
         self.type_checker.check(&ast)?;
         
-        let bytecode = self.compiler.compile(&ast)?;
+        let program = self.compiler.compile(&ast)?;
+        self.warnings = self.compiler.warnings.clone();
         
-        self.execute_bytecode(&bytecode)
+        // An import cycle leading back to the file being run doesn't run it again
+        if let Some(file) = self.current_file.as_ref().and_then(|file| file.canonicalize().ok()) {
+            self.loaded_modules.insert(file.to_string_lossy().into_owned());
+        }
+        self.execute_program(program)
+    }
+
+    /// Returns a key identifying the module at `path` and its source. The
+    /// import handler, when set, supplies (or denies) the module instead of
+    /// the filesystem, which is searched relative to `importer`.
+    fn load_module(&self, path: &str, importer: Option<&Path>) -> Result<(String, String), ASError> {
+        if let Some(handler) = &self.import_handler {
+            return Ok((path.to_string(), handler(path)?));
+        }
+        let resolved_path = self.resolver.resolve(path, importer).map_err(|e| self.error(&format!("Import failed: {}", e)))?;
+        let source = self.resolver.read_file(&resolved_path).map_err(|e| self.error(&format!("Could not read file: {}", e)))?;
+        Ok((resolved_path.to_string_lossy().into_owned(), source))
+    }
+
+    /// Replaces top-level `import` statements with the imported module's own
+    /// statements, recursively. Each module is spliced in only at its first
+    /// import, so its definitions and top-level side effects happen once.
+    fn link_imports(&self, statements: Block, importer: Option<&Path>, linked: &mut HashSet<String>) -> Result<Block, ASError> {
+        let mut result = Vec::with_capacity(statements.len());
+        for stmt in statements {
+            // Named imports keep their module's globals apart, so they aren't spliced
+            if let Statement::Import { path, names: None } = &stmt.node {
+                let (key, source) = self.load_module(path, importer)?;
+                if linked.insert(key.clone()) {
+                    let module = Parser::parse(&source)?;
+                    result.extend(self.link_imports(module.statements, Some(Path::new(&key)), linked)?);
+                }
+            } else {
+                result.push(stmt);
+            }
+        }
+        Ok(result)
     }
 
-    fn execute_bytecode(&mut self, bytecode: &[Opcode]) -> Result<String, ASError> {
+    /// Runs an already compiled program, skipping parsing and type checking.
+    pub fn execute_program(&mut self, program: Program) -> Result<String, ASError> {
+        // A previous run that failed mid-call may have left frames behind
+        self.frames.clear();
+        self.locals.clear();
+        self.switch_globals(None);
+        self.covered_lines.clear();
+        self.coverable_lines.clear();
+        self.instructions = 0;
+        if self.coverage {
+            self.coverable_lines = std::iter::once(&program.main).chain(&program.functions)
+                .flat_map(|code| code.lines.iter().map(|location| location.line))
+                .filter(|&line| line > 0)
+                .collect();
+        }
+        self.functions = program.functions.into_iter().map(Rc::new).collect();
+        self.execute_code(Rc::new(program.main))
+    }
+
+    fn execute_code(&mut self, main: Rc<CodeObject>) -> Result<String, ASError> {
+        let mut code = main;
         let mut pc = 0;
         let mut output = String::new();
+        // Frames below this depth belong to whoever called execute_code (e.g. an import)
+        let base_depth = self.frames.len();
+        self.locals.push(Vec::new());
+        // Whether the previous opcode may have allocated, with a memory limit set
+        let mut check_memory = false;
         
-        while pc < bytecode.len() {
-            let opcode = &bytecode[pc];
+        loop {
+            if let (true, Some(limit)) = (std::mem::take(&mut check_memory), self.memory_limit) {
+                let used = self.estimated_memory();
+                if used > limit {
+                    return Err(self.error(&format!("Memory limit exceeded: using about {} bytes, limit is {}", used, limit)));
+                }
+            }
+            if pc >= code.bytecode.len() {
+                if self.frames.len() == base_depth {
+                    break;
+                }
+                // Falling off the end of a function returns none
+                let frame = self.pop_frame();
+                code = frame.caller;
+                pc = frame.return_pc;
+                self.stack.push(Value::None);
+                continue;
+            }
+            let current = Rc::clone(&code);
+            self.current = Some((Rc::clone(&code), pc));
+            let opcode = &current.bytecode[pc];
+            // Errors raised by this opcode report the statement it was compiled from
+            self.location = current.lines.get(pc).cloned().unwrap_or_else(|| SourceLocation::new(0, 0));
+            if self.coverage && self.location.line > 0 {
+                self.covered_lines.insert(self.location.line);
+            }
+            self.instructions += 1;
+            if let Some(limit) = self.max_instructions {
+                if self.instructions > limit {
+                    return Err(self.error(&format!("Instruction budget exceeded: executed {} opcodes", limit)));
+                }
+            }
+            check_memory = self.memory_limit.is_some() && self.may_allocate(opcode);
             if self.debug {
                 println!("[DEBUG] PC: {:04} | Op: {:?} | Stack: {:?}", pc, opcode, self.stack);
             }
+            if self.trace_capacity > 0 {
+                self.record_trace(pc, opcode);
+            }
             pc += 1;
             
             match opcode {
-                Opcode::LoadConst(n) => self.stack.push(Value::Number(*n)),
-                Opcode::LoadString(s) => self.stack.push(Value::String(s.clone())),
-                Opcode::LoadBool(b) => self.stack.push(Value::Boolean(*b)),
+                Opcode::LoadConst(index) => {
+                    let value = match self.constant(&current, *index)? {
+                        Constant::Integer(n) => Value::Integer(*n),
+                        Constant::Number(n) => Value::Number(*n),
+                        Constant::String(s) => Value::String(s.to_string()),
+                        Constant::Char(c) => Value::Char(*c),
+                        Constant::Boolean(b) => Value::Boolean(*b),
+                    };
+                    self.stack.push(value);
+                },
                 Opcode::LoadVar(name) => {
                     if let Some(val) = self.variables.get(name) {
                         self.stack.push(val.clone());
+                    } else if self.undefined_is_none {
+                        self.stack.push(Value::None);
                     } else {
                         return Err(self.error(&format!("Undefined variable: {}", name)));
                     }
@@ -96,24 +565,72 @@ impl Runtime {
                     let val = self.pop()?;
                     self.variables.insert(name.clone(), val);
                 },
-                Opcode::Output => {
+                Opcode::LoadLocal(slot) => {
+                    let val = self.locals.last().and_then(|slots| slots.get(*slot)).cloned()
+                        .ok_or_else(|| self.error(&format!("Invalid local slot: {}", slot)))?;
+                    self.stack.push(val);
+                },
+                Opcode::StoreLocal(slot) => {
                     let val = self.pop()?;
-                    output.push_str(&format!("{}\n", val));
-                    // Also print to stdout for interactive feel
-                    println!("{}", val);
+                    let slots = self.locals.last_mut().unwrap();
+                    if *slot >= slots.len() {
+                        slots.resize(*slot + 1, Value::None);
+                    }
+                    slots[*slot] = val;
                 },
-                Opcode::Input => {
-                    // Simple input simulation
-                    let mut input = String::new();
-                    std::io::stdin().read_line(&mut input).unwrap(); // Handle error properly in real code
-                    self.stack.push(Value::String(input.trim().to_string()));
+                Opcode::Output(newline) => {
+                    let val = self.pop()?;
+                    let separator = if *newline { '\n' } else { ' ' };
+                    self.write_output(&mut output, &format!("{}{}", val, separator))?;
+                },
+                Opcode::OutputStr(index, newline) => {
+                    let s = match self.constant(&current, *index)? {
+                        Constant::String(s) => s,
+                        other => return Err(self.error(&format!("OutputStr expects a string constant, got {:?}", other))),
+                    };
+                    // Written straight from the pool, with no per-run copy of the text
+                    self.write_output(&mut output, s)?;
+                    self.write_output(&mut output, if *newline { "\n" } else { " " })?;
+                },
+                Opcode::Input(has_prompt) => {
+                    let mut prompt = String::new();
+                    if *has_prompt {
+                        prompt = self.pop()?.to_string();
+                        self.write_output(&mut output, &prompt)?;
+                        // The prompt has no line break, so push it out before blocking
+                        let flushed = match &mut self.output_writer {
+                            Some(writer) => writer.flush(),
+                            None => std::io::Write::flush(&mut std::io::stdout()),
+                        };
+                        flushed.map_err(|e| self.error(&format!("Failed to write output: {}", e)))?;
+                    }
+                    let line = match &mut self.input_handler {
+                        Some(handler) => handler(&prompt)?,
+                        None => {
+                            let mut line = String::new();
+                            let read = std::io::stdin().read_line(&mut line)
+                                .map_err(|e| self.error(&format!("Could not read input: {}", e)))?;
+                            if read == 0 {
+                                return Err(self.error("Could not read input: end of input"));
+                            }
+                            line
+                        }
+                    };
+                    let line = line.trim();
+                    let value = match (line.parse::<i64>(), line.parse::<f64>()) {
+                        (Ok(n), _) if self.numeric_input => Value::Integer(n),
+                        (_, Ok(n)) if self.numeric_input && n.is_finite() => Value::Number(n),
+                        _ => Value::String(line.to_string()),
+                    };
+                    self.stack.push(value);
                 },
                 Opcode::Import(path) => {
-                    // 1. Resolve path
-                    let resolved_path = self.resolver.resolve(path, None).map_err(|e| self.error(&format!("Import failed: {}", e)))?;
-                    
-                    // 2. Read file
-                    let source = self.resolver.read_file(&resolved_path).map_err(|e| self.error(&format!("Could not read file: {}", e)))?;
+                    // 1-2. Resolve and read the module
+                    let (key, source) = self.load_module(path, self.current_file.as_deref())?;
+                    // A module runs once; marking it first also ends import cycles
+                    if !self.loaded_modules.insert(key.clone()) {
+                        continue;
+                    }
                     
                     // 3. Parse
                     let ast = Parser::parse(&source)?;
@@ -122,19 +639,69 @@ impl Runtime {
                     // self.type_checker.check(&ast)?;
 
                     // 5. Compile
-                    // Create a new compiler instance to avoid messing up current bytecode offsets
-                    // or append? For simplicity, we execute recursively.
-                    let mut compiler = Compiler::new();
-                    let bytecode = compiler.compile(&ast)?;
+                    // Functions are separate code objects, so sharing our compiler keeps
+                    // one function table: imported functions get fresh indices.
+                    let program = self.compiler.compile(&ast)?;
+                    self.functions = program.functions.into_iter().map(Rc::new).collect();
                     
-                    // 6. Execute (recursively)
-                    // Save PC and bytecode? No, we are in a loop.
-                    // Best way is to just call execute_bytecode recursively
-                    self.execute_bytecode(&bytecode)?;
+                    // 6. Execute (recursively), resolving its own imports next to it
+                    let importer = self.current_file.replace(PathBuf::from(key));
+                    let result = self.execute_code(Rc::new(program.main));
+                    self.current_file = importer;
+                    result?;
                     
                     // Note: Variables defined in imported file will be in self.variables
                     // effectively acting as a global include.
                 },
+                Opcode::ImportNames(path, names) => {
+                    let (key, source) = self.load_module(path, self.current_file.as_deref())?;
+                    let module = match self.named_modules.get(&key) {
+                        Some(&id) => Some(id),
+                        // A module already imported whole shares the program's globals
+                        None if self.loaded_modules.contains(&key) => None,
+                        None => Some(self.run_named_module(key, &source)?),
+                    };
+                    for name in names {
+                        let Some(value) = self.module_export(module, name) else {
+                            return Err(self.error(&format!("Module '{}' does not define '{}'", path, name)));
+                        };
+                        self.variables.insert(name.clone(), value);
+                    }
+                },
+                Opcode::MakeArray(count) => {
+                    let mut elements = Vec::with_capacity(*count);
+                    for _ in 0..*count {
+                        elements.push(self.pop()?);
+                    }
+                    elements.reverse(); // Elements are popped in reverse order
+                    self.stack.push(Value::Array(elements));
+                },
+                Opcode::MakeMap(count) => {
+                    let mut pairs = Vec::with_capacity(*count);
+                    for _ in 0..*count {
+                        let value = self.pop()?;
+                        match self.pop()? {
+                            Value::String(key) => pairs.push((key, value)),
+                            other => return Err(self.error(&format!("Invalid map key: {}", other))),
+                        }
+                    }
+                    // Pairs were popped last-first; a repeated key keeps its final value
+                    self.stack.push(Value::Map(pairs.into_iter().rev().collect()));
+                },
+                Opcode::GetIndex => {
+                    let index = self.pop()?;
+                    let base = self.pop()?;
+                    let element = self.get_index(base, index)?;
+                    self.stack.push(element);
+                },
+                Opcode::SetIndex => {
+                    // Values are copied, so the updated container is pushed back
+                    let value = self.pop()?;
+                    let index = self.pop()?;
+                    let base = self.pop()?;
+                    let updated = self.set_index(base, index, value)?;
+                    self.stack.push(updated);
+                },
                 Opcode::Pop => {
                     self.pop()?;
                 },
@@ -144,33 +711,81 @@ impl Runtime {
                     let b = self.pop()?;
                     let a = self.pop()?;
                     match (a, b) {
-                        (Value::Number(a), Value::Number(b)) => self.stack.push(Value::Number(a + b)),
                         (Value::String(a), Value::String(b)) => self.stack.push(Value::String(a + &b)),
+                        (a, b) if Self::is_number(&a) && Self::is_number(&b) => {
+                            self.arithmetic(a, b, i64::checked_add, |x, y| x + y)?
+                        }
                         _ => return Err(self.error("Type mismatch for Add")),
                     }
                 },
                 Opcode::Subtract => {
-                     let b = self.pop_number()?;
-                     let a = self.pop_number()?;
-                     self.stack.push(Value::Number(a - b));
+                    let b = self.pop()?;
+                    let a = self.pop()?;
+                    self.arithmetic(a, b, i64::checked_sub, |x, y| x - y)?;
                 },
                 Opcode::Multiply => {
-                     let b = self.pop_number()?;
-                     let a = self.pop_number()?;
-                     self.stack.push(Value::Number(a * b));
+                    let b = self.pop()?;
+                    let a = self.pop()?;
+                    self.arithmetic(a, b, i64::checked_mul, |x, y| x * y)?;
                 },
                 Opcode::Divide => {
-                     let b = self.pop_number()?;
-                     let a = self.pop_number()?;
-                     if b == 0.0 { return Err(self.error("Division by zero")); }
-                     self.stack.push(Value::Number(a / b));
+                    // Division always has float semantics, so `7 / 2` is 3.5
+                    let b = self.pop_number()?;
+                    let a = self.pop_number()?;
+                    if b == 0.0 { return Err(self.error("Division by zero")); }
+                    self.push_number(a / b)?;
+                },
+                Opcode::Modulo => {
+                    let b = self.pop()?;
+                    let a = self.pop()?;
+                    if self.number_value(&b)? == 0.0 { return Err(self.error("Modulo by zero")); }
+                    self.arithmetic(a, b, i64::checked_rem, |x, y| x % y)?;
+                },
+                Opcode::Power => {
+                    let b = self.pop()?;
+                    let a = self.pop()?;
+                    let integer_pow = |x: i64, y: i64| u32::try_from(y).ok().and_then(|y| x.checked_pow(y));
+                    self.arithmetic(a, b, integer_pow, f64::powf)?;
+                },
+                Opcode::Negate => {
+                    match self.pop()? {
+                        Value::Integer(n) => match n.checked_neg() {
+                            Some(negated) => self.push_integer(negated)?,
+                            None => self.push_number(-(n as f64))?,
+                        },
+                        other => {
+                            let n = self.number_value(&other)?;
+                            self.push_number(-n)?;
+                        }
+                    }
+                },
+                Opcode::Not => {
+                    match self.pop()? {
+                        Value::Boolean(b) => self.stack.push(Value::Boolean(!b)),
+                        other => return Err(self.error(&format!("Cannot apply 'not' to {}", builtins::type_name(&other)))),
+                    }
+                },
+                Opcode::BitwiseNot => {
+                    let n = match self.pop()? {
+                        Value::Integer(n) => n,
+                        Value::Number(n) if n.fract() == 0.0 && n.abs() <= i64::MAX as f64 => n as i64,
+                        other => return Err(self.error(&format!("Bitwise not requires an integer, got {}", other))),
+                    };
+                    self.push_integer(!n)?;
                 },
                 
                 // Comparison
                 Opcode::Eq => {
                     let b = self.pop()?;
                     let a = self.pop()?;
-                    self.stack.push(Value::Boolean(a == b));
+                    let equal = self.values_equal(&a, &b);
+                    self.stack.push(Value::Boolean(equal));
+                },
+                Opcode::Ne => {
+                    let b = self.pop()?;
+                    let a = self.pop()?;
+                    let equal = self.values_equal(&a, &b);
+                    self.stack.push(Value::Boolean(!equal));
                 },
                 Opcode::Gt => {
                     let b = self.pop_number()?;
@@ -182,6 +797,16 @@ impl Runtime {
                     let a = self.pop_number()?;
                     self.stack.push(Value::Boolean(a < b));
                 },
+                Opcode::Ge => {
+                    let b = self.pop_number()?;
+                    let a = self.pop_number()?;
+                    self.stack.push(Value::Boolean(a >= b));
+                },
+                Opcode::Le => {
+                    let b = self.pop_number()?;
+                    let a = self.pop_number()?;
+                    self.stack.push(Value::Boolean(a <= b));
+                },
                 
                 // Control Flow
                 Opcode::Jump(target) => {
@@ -189,67 +814,416 @@ impl Runtime {
                 },
                 Opcode::JumpIfFalse(target) => {
                     let val = self.pop()?;
-                    let is_true = match val {
-                        Value::Boolean(b) => b,
-                        Value::Number(n) => n != 0.0,
-                        _ => false,
-                    };
-                    if !is_true {
+                    if !Self::is_truthy(&val) {
                         pc = *target;
                     }
                 },
+                Opcode::JumpIfTrue(target) => {
+                    let val = self.pop()?;
+                    if Self::is_truthy(&val) {
+                        pc = *target;
+                    }
+                },
+                Opcode::JumpRel(offset) => {
+                    pc = self.relative_target(pc - 1, *offset)?;
+                },
+                Opcode::JumpIfFalseRel(offset) => {
+                    let val = self.pop()?;
+                    if !Self::is_truthy(&val) {
+                        pc = self.relative_target(pc - 1, *offset)?;
+                    }
+                },
+                Opcode::JumpIfTrueRel(offset) => {
+                    let val = self.pop()?;
+                    if Self::is_truthy(&val) {
+                        pc = self.relative_target(pc - 1, *offset)?;
+                    }
+                },
                 
                 // Function Calls
+                Opcode::LoadFunction(index) => {
+                    let function = match self.functions.get(*index) {
+                        Some(code) => FunctionRef { index: *index, name: Some(code.name.clone()), arity: code.arity },
+                        None => return Err(self.error(&format!("Invalid function index: {}", index))),
+                    };
+                    self.stack.push(Value::Function(function));
+                },
+                Opcode::CallFunction(index, arg_count) => {
+                    let args = self.pop_args(*arg_count)?;
+                    let callee = match self.functions.get(*index) {
+                        Some(function) => Rc::clone(function),
+                        None => return Err(self.error(&format!("Invalid function index: {}", index))),
+                    };
+                    let locals = self.bind_arguments(&callee, args)?;
+                    let caller = std::mem::replace(&mut code, callee);
+                    self.push_frame(caller, pc, *index, locals)?;
+                    pc = 0;
+                },
                 Opcode::Call(name, arg_count) => {
-                    let mut args = Vec::new();
-                    for _ in 0..*arg_count {
-                        args.push(self.pop()?);
-                    }
-                    args.reverse(); // Arguments are popped in reverse order
+                    let args = self.pop_args(*arg_count)?;
 
                     if name == "print" {
                         // Built-in print function
                         let output_str: Vec<String> = args.iter().map(|v| v.to_string()).collect();
                         let line = output_str.join(" ");
-                        println!("{}", line);
-                        output.push_str(&format!("{}\n", line));
+                        self.write_output(&mut output, &format!("{}\n", line))?;
                         self.stack.push(Value::None); // print returns None
+                    } else if let Some(builtin) = builtins::lookup(name) {
+                        let result = builtin(&args).map_err(|e| self.error(&e))?;
+                        self.stack.push(result);
+                    } else if let Some(index) = self.function_named(name) {
+                        let callee = match self.functions.get(index) {
+                            Some(function) => Rc::clone(function),
+                            None => return Err(self.error(&format!("Invalid function index: {}", index))),
+                        };
+                        let locals = self.bind_arguments(&callee, args)?;
+                        let caller = std::mem::replace(&mut code, callee);
+                        self.push_frame(caller, pc, index, locals)?;
+                        pc = 0;
                     } else {
                         return Err(self.error(&format!("Function '{}' not defined or supported in this runtime version", name)));
                     }
                 },
                 Opcode::Return => {
-                    // refined return logic to be added with stack frames
-                    // for now, just end or no-op if at top level? 
-                    // Use a simple return for now, maybe pop if value on stack? 
-                    // If return has a value, it's on the stack.
-                    // effectively, we stop executing this bytecode sequence?
-                    // But we are in a loop `while pc < bytecode.len()`.
-                    // We should break?
-                    // But wait, `compile_function` pushes `Return` at end.
-                    return Ok(output);
+                    if self.frames.len() == base_depth {
+                        // A top-level return ends the program
+                        self.locals.pop();
+                        return Ok(output);
+                    }
+                    let value = self.pop()?;
+                    let frame = self.pop_frame();
+                    code = frame.caller;
+                    pc = frame.return_pc;
+                    self.stack.push(value);
                 },
 
                 _ => return Err(self.error(&format!("Opcode not implemented: {:?}", opcode))),
             }
         }
         
+        self.locals.pop();
         Ok(output)
     }
     
+    fn values_equal(&self, a: &Value, b: &Value) -> bool {
+        match (a, b, self.float_epsilon) {
+            (Value::Integer(x), Value::Integer(y), _) => x == y,
+            (x, y, epsilon) if Self::is_number(x) && Self::is_number(y) => {
+                let (x, y) = (Self::as_float(x), Self::as_float(y));
+                match epsilon {
+                    Some(epsilon) => builtins::approx_equal(x, y, epsilon),
+                    None => x == y,
+                }
+            }
+            _ => a == b,
+        }
+    }
+
+    /// Whether running `opcode` on the current stack can grow the memory
+    /// `estimated_memory` counts, which is all the memory limit checks.
+    fn may_allocate(&self, opcode: &Opcode) -> bool {
+        match opcode {
+            Opcode::MakeArray(_) | Opcode::MakeMap(_) | Opcode::SetIndex
+            | Opcode::StoreVar(_) | Opcode::StoreLocal(_)
+            | Opcode::Call(..) | Opcode::CallFunction(..)
+            | Opcode::Input(_) | Opcode::Import(_) | Opcode::ImportNames(..) => true,
+            // Only concatenation allocates; numbers are added in place
+            Opcode::Add => self.stack.iter().rev().take(2)
+                .any(|value| matches!(value, Value::String(_) | Value::Array(_) | Value::Map(_) | Value::Bytes(_))),
+            _ => false,
+        }
+    }
+
+    fn constant<'a>(&self, code: &'a CodeObject, index: usize) -> Result<&'a Constant, ASError> {
+        code.constants.get(index)
+            .ok_or_else(|| self.error(&format!("Invalid constant index: {}", index)))
+    }
+
+    fn is_truthy(value: &Value) -> bool {
+        match value {
+            Value::Boolean(b) => *b,
+            Value::Integer(n) => *n != 0,
+            Value::Number(n) => *n != 0.0,
+            _ => false,
+        }
+    }
+
+    fn relative_target(&self, jump_pc: usize, offset: isize) -> Result<usize, ASError> {
+        jump_pc.checked_add_signed(offset)
+            .ok_or_else(|| self.error(&format!("Relative jump {} from {} leaves the code object", offset, jump_pc)))
+    }
+
+    /// Pops `count` call arguments, returning them in source order.
+    fn pop_args(&mut self, count: usize) -> Result<Vec<Value>, ASError> {
+        let mut args = Vec::with_capacity(count);
+        for _ in 0..count {
+            args.push(self.pop()?);
+        }
+        args.reverse();
+        Ok(args)
+    }
+
+    /// Enters the function at `index`, called from `caller`, whose local
+    /// slots start out as `locals`.
+    fn push_frame(&mut self, caller: Rc<CodeObject>, return_pc: usize, index: usize, locals: Vec<Value>) -> Result<(), ASError> {
+        if self.frames.len() >= self.max_call_depth {
+            return Err(self.error(&format!("Maximum call depth of {} exceeded", self.max_call_depth)));
+        }
+        self.frames.push(CallFrame { caller, return_pc, caller_module: self.current_module });
+        self.locals.push(locals);
+        self.switch_globals(self.function_modules.get(&index).copied());
+        Ok(())
+    }
+
+    /// Leaves the innermost function call, returning its frame.
+    fn pop_frame(&mut self) -> CallFrame {
+        let frame = self.frames.pop().unwrap();
+        self.locals.pop();
+        self.switch_globals(frame.caller_module);
+        frame
+    }
+
+    /// Makes `variables` hold the globals of `module`, putting the current
+    /// ones back where they belong.
+    fn switch_globals(&mut self, module: Option<usize>) {
+        if module == self.current_module {
+            return;
+        }
+        let current = std::mem::take(&mut self.variables);
+        match self.current_module {
+            Some(id) => self.module_globals[id] = current,
+            None => self.program_globals = current,
+        }
+        self.variables = match module {
+            Some(id) => std::mem::take(&mut self.module_globals[id]),
+            None => std::mem::take(&mut self.program_globals),
+        };
+        self.current_module = module;
+    }
+
+    /// The function a by-name call reaches: one held by a global of that
+    /// name, such as a named import, else the latest function so named
+    /// outside modules imported by name.
+    fn function_named(&self, name: &str) -> Option<usize> {
+        if let Some(Value::Function(function)) = self.variables.get(name) {
+            return Some(function.index);
+        }
+        self.functions.iter().enumerate().rev()
+            .find(|(index, function)| function.name == name && !self.function_modules.contains_key(index))
+            .map(|(index, _)| index)
+    }
+
+    /// Compiles and runs a module imported by name with globals of its own,
+    /// returning its module id.
+    fn run_named_module(&mut self, key: String, source: &str) -> Result<usize, ASError> {
+        let ast = Parser::parse(source)?;
+        let first_function = self.compiler.functions.len();
+        let program = self.compiler.compile_isolated(&ast)?;
+        let id = self.module_globals.len();
+        self.module_globals.push(HashMap::new());
+        // Registered before running, so an import cycle back to it binds what it has defined so far
+        self.named_modules.insert(key.clone(), id);
+        self.loaded_modules.insert(key.clone());
+        for index in first_function..program.functions.len() {
+            self.function_modules.insert(index, id);
+        }
+        self.functions = program.functions.into_iter().map(Rc::new).collect();
+        
+        let importer_module = self.current_module;
+        let importer_file = self.current_file.replace(PathBuf::from(key));
+        self.switch_globals(Some(id));
+        let result = self.execute_code(Rc::new(program.main));
+        self.switch_globals(importer_module);
+        self.current_file = importer_file;
+        result.map(|_| id)
+    }
+
+    /// The value of `name` in `module`: one of its globals, else a function
+    /// it defines. `None` stands for modules imported whole, which share the
+    /// program's globals.
+    fn module_export(&self, module: Option<usize>, name: &str) -> Option<Value> {
+        let globals = match module {
+            Some(id) if self.current_module != Some(id) => &self.module_globals[id],
+            None if self.current_module.is_some() => &self.program_globals,
+            _ => &self.variables,
+        };
+        if let Some(value) = globals.get(name) {
+            return Some(value.clone());
+        }
+        self.functions.iter().enumerate().rev()
+            .find(|(index, function)| function.name == name && self.function_modules.get(index).copied() == module)
+            .map(|(index, function)| Value::Function(FunctionRef { index, name: Some(function.name.clone()), arity: function.arity }))
+    }
+
+    /// Checks the argument count; the arguments become the callee's first
+    /// local slots.
+    fn bind_arguments(&self, callee: &CodeObject, args: Vec<Value>) -> Result<Vec<Value>, ASError> {
+        if args.len() != callee.arity {
+            return Err(self.error(&format!(
+                "Function '{}' expects {} argument(s), got {}",
+                callee.name, callee.arity, args.len()
+            )));
+        }
+        Ok(args)
+    }
+
+    fn record_trace(&mut self, pc: usize, opcode: &Opcode) {
+        if self.trace.len() == self.trace_capacity {
+            self.trace.pop_front();
+        }
+        self.trace.push_back(TraceEntry {
+            pc,
+            opcode: opcode.clone(),
+            stack_depth: self.stack.len(),
+            stack_top: self.stack.last().cloned(),
+        });
+    }
+    
+    fn push_number(&mut self, n: f64) -> Result<(), ASError> {
+        if self.strict_integers && n.abs() > MAX_SAFE_INTEGER {
+            return Err(self.error(&format!("Integer overflow: {} exceeds the safe integer range", n)));
+        }
+        self.stack.push(Value::Number(n));
+        Ok(())
+    }
+
+    fn push_integer(&mut self, n: i64) -> Result<(), ASError> {
+        if self.strict_integers && n.unsigned_abs() > MAX_SAFE_INTEGER as u64 {
+            return Err(self.error(&format!("Integer overflow: {} exceeds the safe integer range", n)));
+        }
+        self.stack.push(Value::Integer(n));
+        Ok(())
+    }
+
+    fn is_number(value: &Value) -> bool {
+        matches!(value, Value::Integer(_) | Value::Number(_))
+    }
+
+    /// The numeric value of an `Integer` or `Number`; callers check
+    /// `is_number` first.
+    fn as_float(value: &Value) -> f64 {
+        match value {
+            Value::Integer(n) => *n as f64,
+            Value::Number(n) => *n,
+            _ => f64::NAN,
+        }
+    }
+
+    fn number_value(&self, value: &Value) -> Result<f64, ASError> {
+        if Self::is_number(value) {
+            Ok(Self::as_float(value))
+        } else {
+            Err(self.error("Expected number"))
+        }
+    }
+
+    /// Two integers stay integral unless `integer_op` overflows (returns
+    /// `None`); any other numeric pair is promoted to a float.
+    fn arithmetic(
+        &mut self,
+        a: Value,
+        b: Value,
+        integer_op: impl Fn(i64, i64) -> Option<i64>,
+        float_op: impl Fn(f64, f64) -> f64,
+    ) -> Result<(), ASError> {
+        if let (Value::Integer(x), Value::Integer(y)) = (&a, &b) {
+            if let Some(n) = integer_op(*x, *y) {
+                return self.push_integer(n);
+            }
+        }
+        let result = float_op(self.number_value(&a)?, self.number_value(&b)?);
+        self.push_number(result)
+    }
+    
     fn pop(&mut self) -> Result<Value, ASError> {
-        self.stack.pop().ok_or_else(|| self.error("Stack underflow"))
+        self.stack.pop().ok_or_else(|| self.stack_underflow())
     }
     
     fn pop_number(&mut self) -> Result<f64, ASError> {
-        match self.pop()? {
-            Value::Number(n) => Ok(n),
-            _ => Err(self.error("Expected number")),
+        let value = self.pop()?;
+        self.number_value(&value)
+    }
+    
+    /// Resolves an element index against a sequence of length `len`.
+    /// Negative indices count from the end, so `-1` is the last element and
+    /// `-len` the first. Future slice bounds should follow the same rule.
+    fn resolve_index(&self, index: &Value, len: usize) -> Result<usize, ASError> {
+        let n = match index {
+            Value::Integer(n) => *n as f64,
+            Value::Number(n) if n.fract() == 0.0 => *n,
+            other => return Err(self.error(&format!("Invalid index: {}", other))),
+        };
+        let resolved = if n < 0.0 { n + len as f64 } else { n };
+        if resolved < 0.0 || resolved >= len as f64 {
+            return Err(self.error(&format!("Index {} out of bounds for length {}", n, len)));
+        }
+        Ok(resolved as usize)
+    }
+    
+    fn get_index(&self, base: Value, index: Value) -> Result<Value, ASError> {
+        match base {
+            Value::Map(mut entries) => match index {
+                Value::String(key) => entries.remove(&key).ok_or_else(|| self.error(&format!("Key '{}' not found", key))),
+                other => Err(self.error(&format!("Invalid map key: {}", other))),
+            },
+            Value::Array(mut elements) => {
+                let position = self.resolve_index(&index, elements.len())?;
+                Ok(elements.swap_remove(position))
+            }
+            Value::String(s) => {
+                let position = self.resolve_index(&index, s.chars().count())?;
+                Ok(Value::String(s.chars().nth(position).unwrap().to_string()))
+            }
+            Value::Bytes(bytes) => {
+                let position = self.resolve_index(&index, bytes.len())?;
+                Ok(Value::Integer(bytes[position] as i64))
+            }
+            other => Err(self.error(&format!("Cannot index into {}", other))),
+        }
+    }
+    
+    /// Returns `base` with the element at `index` replaced by `value`.
+    fn set_index(&self, base: Value, index: Value, value: Value) -> Result<Value, ASError> {
+        match base {
+            Value::Map(mut entries) => match index {
+                Value::String(key) => {
+                    entries.insert(key, value);
+                    Ok(Value::Map(entries))
+                }
+                other => Err(self.error(&format!("Invalid map key: {}", other))),
+            },
+            Value::Array(mut elements) => {
+                let position = self.resolve_index(&index, elements.len())?;
+                elements[position] = value;
+                Ok(Value::Array(elements))
+            }
+            Value::Bytes(mut bytes) => {
+                let position = self.resolve_index(&index, bytes.len())?;
+                bytes[position] = match value {
+                    Value::Integer(n) if (0..=255).contains(&n) => n as u8,
+                    Value::Number(n) if n.fract() == 0.0 && (0.0..=255.0).contains(&n) => n as u8,
+                    other => return Err(self.error(&format!("Byte value must be an integer from 0 to 255, got {}", other))),
+                };
+                Ok(Value::Bytes(bytes))
+            }
+            other => Err(self.error(&format!("Cannot assign an index of {}", other))),
+        }
+    }
+    
+    /// Popping an empty stack means the bytecode is malformed, so name the
+    /// offending opcode to make the report actionable.
+    fn stack_underflow(&self) -> ASError {
+        match &self.current {
+            Some((code, pc)) => self.error(&format!(
+                "Stack underflow at {}:{:04} while executing {:?} (likely a compiler bug)",
+                code.name, pc, code.bytecode[*pc]
+            )),
+            None => self.error("Stack underflow"),
         }
     }
     
     fn error(&self, msg: &str) -> ASError {
-        ASError::new(ErrorKind::RuntimeError, msg.to_string(), SourceLocation::new(0, 0))
+        ASError::new(ErrorKind::RuntimeError, msg.to_string(), self.location.clone())
     }
 }
 
@@ -1,17 +1,22 @@
 // Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
 
-use crate::parser::{AST, Statement, Expression, BinaryOp, UnaryOp, Parser};
-use crate::error::{ASError, ErrorKind, SourceLocation};
-use crate::resolver::Resolver;
+use crate::parser::{AST, Block, Statement, Expression, BinaryOp, UnaryOp, Parser};
+use crate::error::{ASError, ErrorKind, SourceLocation, Span};
+use crate::resolver::{ImportHandler, Resolver};
 use std::collections::{HashMap, HashSet};
+use serde::Serialize;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum Type {
     Number,
     String,
     Boolean,
+    Char,
     Array(Box<Type>),
+    /// String-keyed map whose values have the inner type.
+    Map(Box<Type>),
+    Bytes,
     Function {
         params: Vec<Type>,
         returns: Box<Type>,
@@ -27,7 +32,10 @@ impl std::fmt::Display for Type {
             Type::Number => write!(f, "Number"),
             Type::String => write!(f, "String"),
             Type::Boolean => write!(f, "Boolean"),
+            Type::Char => write!(f, "Char"),
             Type::Array(inner) => write!(f, "Array<{}>", inner),
+            Type::Map(inner) => write!(f, "Map<{}>", inner),
+            Type::Bytes => write!(f, "Bytes"),
             Type::Function { params, returns } => {
                 write!(f, "fn(")?;
                 for (i, p) in params.iter().enumerate() {
@@ -43,26 +51,106 @@ impl std::fmt::Display for Type {
     }
 }
 
+/// Signatures of the runtime's built-in functions.
+pub(crate) fn builtin_signature(name: &str) -> Option<Type> {
+    let (params, returns) = match name {
+        "keys" => (vec![Type::Any], Type::Array(Box::new(Type::String))),
+        "values" | "entries" => (vec![Type::Any], Type::Array(Box::new(Type::Any))),
+        "has_key" => (vec![Type::Any, Type::String], Type::Boolean),
+        "remove" => (vec![Type::Any, Type::String], Type::Any),
+        "get" => (vec![Type::Any, Type::String, Type::Any], Type::Any),
+        "unique" => (vec![Type::Any], Type::Array(Box::new(Type::Any))),
+        "base64_encode" => (vec![Type::Any], Type::String),
+        "base64_decode" => (vec![Type::String], Type::Bytes),
+        "typeof" => (vec![Type::Any], Type::String),
+        "str" | "to_string" => (vec![Type::Any], Type::String),
+        "to_number" => (vec![Type::String], Type::Number),
+        "len" => (vec![Type::Any], Type::Number),
+        "sqrt" | "abs" | "floor" | "ceil" | "round" => (vec![Type::Number], Type::Number),
+        "min" | "max" | "pow" => (vec![Type::Number, Type::Number], Type::Number),
+        "upper" | "lower" | "trim" => (vec![Type::String], Type::String),
+        "split" => (vec![Type::String, Type::String], Type::Array(Box::new(Type::String))),
+        "join" => (vec![Type::Array(Box::new(Type::String)), Type::String], Type::String),
+        "substring" => (vec![Type::String, Type::Number, Type::Number], Type::String),
+        "validate" => (vec![Type::Any, Type::Any], Type::Array(Box::new(Type::String))),
+        // Variadic: only the template is listed
+        "format" => (vec![Type::String], Type::String),
+        "approx_eq" => (vec![Type::Number, Type::Number, Type::Number], Type::Boolean),
+        _ => return None,
+    };
+    Some(Type::Function { params, returns: Box::new(returns) })
+}
+
+/// A name bound while checking, with its type and the statement binding it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub name: String,
+    pub ty: Type,
+    pub span: Span,
+}
+
 pub struct TypeChecker {
-    variables: HashMap<String, Type>,
+    /// Variable types of each open block, innermost last; the first scope
+    /// holds globals and persists across `check` calls.
+    variables: Vec<HashMap<String, Type>>,
     functions: HashMap<String, Type>,
+    /// Declared return type of the function whose body is being checked.
+    return_type: Option<Type>,
     resolver: Resolver,
     visited: HashSet<PathBuf>,
+    /// Type unknown variables as `Any` instead of reporting them.
+    pub allow_undefined: bool,
+    /// Loads imported modules instead of the filesystem resolver when set.
+    pub import_handler: Option<ImportHandler>,
+    /// Path of the file being checked, if any; its relative imports resolve
+    /// against its directory instead of the working directory.
+    pub current_file: Option<PathBuf>,
+    /// Record every declaration into `declarations`, for editor tooling.
+    pub record_declarations: bool,
+    /// Declarations seen by the last `check`, in order, when recorded.
+    pub declarations: Vec<Declaration>,
+    /// Span of the statement being checked.
+    span: Option<Span>,
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TypeChecker {
     pub fn new() -> Self {
         TypeChecker {
-            variables: HashMap::new(),
+            variables: vec![HashMap::new()],
             functions: HashMap::new(),
+            return_type: None,
             resolver: Resolver::new(),
             visited: HashSet::new(),
+            allow_undefined: false,
+            import_handler: None,
+            current_file: None,
+            record_declarations: false,
+            declarations: Vec::new(),
+            span: None,
         }
     }
 
+    /// Declare a variable bound outside the program, such as a global
+    /// injected by an embedder.
+    pub fn define_variable(&mut self, name: &str, ty: Type) {
+        self.variables[0].insert(name.to_string(), ty);
+    }
+
     pub fn check(&mut self, ast: &AST) -> Result<(), ASError> {
+        // A previous check that failed inside a block may have left its scope open
+        self.variables.truncate(1);
+        self.return_type = None;
+        self.declarations.clear();
+        self.span = None;
         for statement in &ast.statements {
-            self.check_statement(statement)?;
+            self.span = Some(statement.span.clone());
+            self.check_statement(&statement.node)?;
         }
         Ok(())
     }
@@ -79,14 +167,27 @@ impl TypeChecker {
                             annotated, inferred
                         )));
                     }
-                    self.variables.insert(name.clone(), annotated.clone());
+                    self.declare_variable(name, annotated.clone());
                 } else {
-                    self.variables.insert(name.clone(), inferred);
+                    self.declare_variable(name, inferred);
                 }
                 Ok(())
             }
-            Statement::Output(expr) => {
-                self.infer_type(expr)?;
+            Statement::Output(exprs) => {
+                for expr in exprs {
+                    self.infer_type(expr)?;
+                }
+                Ok(())
+            }
+            Statement::Input { prompt, target } => {
+                if let Some(p) = prompt {
+                    self.infer_type(p)?;
+                }
+                // Input may be a number or a string depending on runtime settings
+                match self.variables.iter_mut().rev().find_map(|scope| scope.get_mut(target)) {
+                    Some(existing) => *existing = Type::Any,
+                    None => self.declare_variable(target, Type::Any),
+                }
                 Ok(())
             }
             Statement::If { condition, then_branch, elif_branches, else_branch } => {
@@ -98,24 +199,18 @@ impl TypeChecker {
                     )));
                 }
                 
-                for stmt in then_branch {
-                    self.check_statement(stmt)?;
-                }
+                self.check_block(then_branch)?;
                 
                 for (elif_cond, elif_body) in elif_branches {
                     let elif_type = self.infer_type(elif_cond)?;
                     if elif_type != Type::Boolean && elif_type != Type::Any {
                         return Err(self.error("Elif condition must be Boolean"));
                     }
-                    for stmt in elif_body {
-                        self.check_statement(stmt)?;
-                    }
+                    self.check_block(elif_body)?;
                 }
                 
                 if let Some(else_stmts) = else_branch {
-                    for stmt in else_stmts {
-                        self.check_statement(stmt)?;
-                    }
+                    self.check_block(else_stmts)?;
                 }
                 Ok(())
             }
@@ -124,40 +219,97 @@ impl TypeChecker {
                 if cond_type != Type::Boolean && cond_type != Type::Any {
                     return Err(self.error("While condition must be Boolean"));
                 }
-                for stmt in body {
-                    self.check_statement(stmt)?;
+                self.check_block(body)
+            }
+            Statement::For { init, condition, update, body } => {
+                // The loop variable is scoped to the loop
+                self.variables.push(HashMap::new());
+                if let Some(init_stmt) = init {
+                    self.check_statement(init_stmt)?;
                 }
+                if let Some(cond) = condition {
+                    let cond_type = self.infer_type(cond)?;
+                    if cond_type != Type::Boolean && cond_type != Type::Any {
+                        return Err(self.error("For condition must be Boolean"));
+                    }
+                }
+                self.check_block(body)?;
+                if let Some(upd) = update {
+                    self.check_statement(upd)?;
+                }
+                self.variables.pop();
                 Ok(())
             }
-            Statement::Function { name, params, body, return_type } => {
-                // Create function signature
-                let param_types = params.iter().map(|_| Type::Any).collect();
+            Statement::Function { name, params, param_types, body, return_type } => {
+                // Create function signature; unannotated parameters accept anything
+                let param_types: Vec<Type> = param_types.iter()
+                    .map(|ty| ty.clone().unwrap_or(Type::Any))
+                    .collect();
                 let ret_type = return_type.clone().unwrap_or(Type::Any);
                 
-                self.functions.insert(name.clone(), Type::Function {
-                    params: param_types,
+                let signature = Type::Function {
+                    params: param_types.clone(),
                     returns: Box::new(ret_type),
-                });
+                };
+                self.record(name, &signature);
+                self.functions.insert(name.clone(), signature);
                 
-                // Type check body (simplified - doesn't add params to scope)
-                for stmt in body {
-                    self.check_statement(stmt)?;
+                // The body's variables are local to it
+                for (param, ty) in params.iter().zip(&param_types) {
+                    self.record(param, ty);
                 }
-                Ok(())
+                // Functions don't capture: enclosing block and function locals
+                // are out of reach at runtime, so only globals stay visible
+                let enclosing = self.variables.split_off(1);
+                self.variables.push(params.iter().cloned().zip(param_types).collect());
+                let outer_return_type = std::mem::replace(&mut self.return_type, return_type.clone());
+                let result = self.check_block(body);
+                self.return_type = outer_return_type;
+                self.variables.truncate(1);
+                self.variables.extend(enclosing);
+                result
             }
-            Statement::Import { path } => {
-                let resolved = self.resolver.resolve(path, None).map_err(|e| self.error(&format!("Import failed: {}", e)))?;
-                if self.visited.contains(&resolved) { return Ok(()); }
-                self.visited.insert(resolved.clone());
-                
-                let source = self.resolver.read_file(&resolved).map_err(|e| self.error(&format!("Read failed: {}", e)))?;
+            Statement::Import { path, names } => {
+                let Some((module_path, source)) = self.load_unvisited(path)? else {
+                    // Checked before; names imported from it again stay untyped
+                    for name in names.iter().flatten() {
+                        self.declare_variable(name, Type::Any);
+                    }
+                    return Ok(());
+                };
                 // Parse the imported file
                 let ast = Parser::parse(&source)?;
                 
-                // Recursively check the imported AST
-                // Variables defined in the imported file will be added to self.variables
-                self.check(&ast)?;
-                
+                // Recursively check the imported AST, whose own imports resolve next to it
+                let importer = self.current_file.replace(module_path);
+                let result = match names {
+                    // Variables defined in the imported file join the importing scope
+                    None => ast.statements.iter().try_for_each(|statement| self.check_statement(&statement.node)),
+                    Some(names) => self.check_named_import(&ast, names),
+                };
+                self.current_file = importer;
+                result
+            }
+            Statement::ExpressionStmt(expr) => {
+                self.infer_type(expr)?;
+                Ok(())
+            }
+            Statement::Return(value) => {
+                let returned = match value {
+                    Some(expr) => self.infer_type(expr)?,
+                    None => Type::Void,
+                };
+                // Unannotated functions may return anything
+                let Some(expected) = &self.return_type else { return Ok(()) };
+                if value.is_none() && *expected != Type::Void {
+                    return Err(self.error(&format!("Missing return value: function returns {}", expected)));
+                }
+                if !self.types_compatible(expected, &returned) {
+                    return Err(self.error(&format!(
+                        "Type mismatch: function returns {}, got {}",
+                        expected, returned
+                    )));
+                }
                 Ok(())
             }
             _ => Ok(()), // Other statements pass through
@@ -168,11 +320,21 @@ impl TypeChecker {
         match expr {
             Expression::Number(_) => Ok(Type::Number),
             Expression::String(_) => Ok(Type::String),
+            Expression::Char(_) => Ok(Type::Char),
             Expression::Boolean(_) => Ok(Type::Boolean),
             Expression::Identifier(name) => {
-                self.variables.get(name)
-                    .cloned()
-                    .ok_or_else(|| self.error(&format!("Undefined variable: {}", name)))
+                match self.lookup_variable(name).or_else(|| self.functions.get(name)) {
+                    Some(t) => Ok(t.clone()),
+                    // `_1` is a valid name, but unbound `_100` is a number with a misplaced separator
+                    None if name.starts_with('_') && name.bytes().any(|b| b.is_ascii_digit())
+                        && name.bytes().all(|b| b == b'_' || b.is_ascii_digit()) => {
+                        let mut err = self.error(&format!("Invalid number: {}", name));
+                        err.kind = ErrorKind::SyntaxError;
+                        Err(err)
+                    }
+                    None if self.allow_undefined => Ok(Type::Any),
+                    None => Err(self.error(&format!("Undefined variable: {}", name))),
+                }
             }
             Expression::BinaryOp { left, operator, right } => {
                 let left_type = self.infer_type(left)?;
@@ -185,6 +347,9 @@ impl TypeChecker {
                             Ok(Type::Number)
                         } else if left_type == Type::String && right_type == Type::String && *operator == BinaryOp::Add {
                             Ok(Type::String)
+                        } else if left_type == Type::Any || right_type == Type::Any {
+                            // Checked at runtime once the operand's value is known
+                            Ok(Type::Any)
                         } else {
                             Err(self.error(&format!(
                                 "Cannot apply {:?} to {} and {}",
@@ -206,57 +371,238 @@ impl TypeChecker {
                     _ => Ok(Type::Any),
                 }
             }
+            Expression::Grouping(inner) => self.infer_type(inner),
             Expression::UnaryOp { operator, operand } => {
                 let operand_type = self.infer_type(operand)?;
                 match operator {
                     UnaryOp::Negate => {
-                        if operand_type == Type::Number {
+                        if operand_type == Type::Number || operand_type == Type::Any {
                             Ok(Type::Number)
                         } else {
                             Err(self.error("Cannot negate non-number"))
                         }
                     }
                     UnaryOp::Not => {
-                        if operand_type == Type::Boolean {
+                        if operand_type == Type::Boolean || operand_type == Type::Any {
                             Ok(Type::Boolean)
                         } else {
                             Err(self.error("Cannot apply 'not' to non-boolean"))
                         }
                     }
+                    UnaryOp::BitwiseNot => {
+                        if operand_type == Type::Number || operand_type == Type::Any {
+                            Ok(Type::Number)
+                        } else {
+                            Err(self.error("Cannot apply '~' to non-number"))
+                        }
+                    }
                     _ => Ok(operand_type),
                 }
             }
-            Expression::Call { function, arguments: _ } => {
+            Expression::Call { function, arguments } => {
+                let argument_types = arguments.iter()
+                    .map(|arg| self.infer_type(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
                 if let Expression::Identifier(name) = &**function {
-                    if let Some(Type::Function { returns, .. }) = self.functions.get(name) {
+                    let signature = self.functions.get(name).cloned().or_else(|| builtin_signature(name));
+                    if let Some(Type::Function { params, returns }) = &signature {
+                        // `format` is variadic; its signature lists only the template
+                        if name == "format" && !self.functions.contains_key(name) {
+                            return Ok(*returns.clone());
+                        }
+                        if params.len() != argument_types.len() {
+                            return Err(self.error(&format!(
+                                "Function '{}' expects {} argument(s), got {}",
+                                name, params.len(), argument_types.len()
+                            )));
+                        }
+                        for (i, (param, argument)) in params.iter().zip(&argument_types).enumerate() {
+                            if !self.types_compatible(param, argument) {
+                                return Err(self.error(&format!(
+                                    "Type mismatch: argument {} of '{}' expects {}, got {}",
+                                    i + 1, name, param, argument
+                                )));
+                            }
+                        }
                         Ok(*returns.clone())
                     } else {
-                        Ok(Type::Any) // Built-in or unknown function
+                        Ok(Type::Any) // Unknown function
                     }
                 } else {
                     Ok(Type::Any)
                 }
             }
+            Expression::Index { array, index } => {
+                let base_type = self.infer_type(array)?;
+                let index_type = self.infer_type(index)?;
+                if let Type::Map(value) = base_type {
+                    if index_type != Type::String && index_type != Type::Any {
+                        return Err(self.error(&format!("Map key must be String, got {}", index_type)));
+                    }
+                    return Ok(*value);
+                }
+                if index_type != Type::Number && index_type != Type::Any {
+                    return Err(self.error(&format!("Index must be Number, got {}", index_type)));
+                }
+                match base_type {
+                    Type::Array(element) => Ok(*element),
+                    Type::String => Ok(Type::String),
+                    Type::Bytes => Ok(Type::Number),
+                    Type::Any => Ok(Type::Any),
+                    other => Err(self.error(&format!("Cannot index into {}", other))),
+                }
+            }
             Expression::Array { elements } => {
-                if elements.is_empty() {
-                    Ok(Type::Array(Box::new(Type::Any)))
-                } else {
-                    let first_type = self.infer_type(&elements[0])?;
-                    Ok(Type::Array(Box::new(first_type)))
+                let mut element_type = Type::Any;
+                for (i, element) in elements.iter().enumerate() {
+                    let ty = self.infer_type(element)?;
+                    element_type = match Self::unify(&element_type, &ty) {
+                        Some(unified) => unified,
+                        None => return Err(self.error(&format!(
+                            "Array elements must share a type: element {} is {}, expected {}",
+                            i, ty, element_type
+                        ))),
+                    };
                 }
+                Ok(Type::Array(Box::new(element_type)))
+            }
+            Expression::Map { entries } => {
+                let mut value_type = Type::Any;
+                for (key, value) in entries {
+                    let ty = self.infer_type(value)?;
+                    value_type = match Self::unify(&value_type, &ty) {
+                        Some(unified) => unified,
+                        None => return Err(self.error(&format!(
+                            "Map values must share a type: value for '{}' is {}, expected {}",
+                            key, ty, value_type
+                        ))),
+                    };
+                }
+                Ok(Type::Map(Box::new(value_type)))
+            }
+            Expression::Assign { target, value } => {
+                let Some(declared) = self.lookup_variable(target) else {
+                    return Err(self.error(&format!("Cannot assign to undeclared variable '{}'", target)));
+                };
+                let assigned = self.infer_type(value)?;
+                if !self.types_compatible(declared, &assigned) {
+                    return Err(self.error(&format!(
+                        "Type mismatch: cannot assign {} to '{}' of type {}",
+                        assigned, target, declared
+                    )));
+                }
+                Ok(declared.clone())
+            }
+            Expression::IndexAssign { target, index, value } => {
+                if self.lookup_variable(target).is_none() {
+                    return Err(self.error(&format!("Cannot assign to undeclared variable '{}'", target)));
+                }
+                let element = self.infer_type(&Expression::Index {
+                    array: Box::new(Expression::Identifier(target.clone())),
+                    index: index.clone(),
+                })?;
+                let assigned = self.infer_type(value)?;
+                if !self.types_compatible(&element, &assigned) {
+                    return Err(self.error(&format!(
+                        "Type mismatch: cannot assign {} to an element of '{}' of type {}",
+                        assigned, target, element
+                    )));
+                }
+                Ok(Type::Void)
             }
-            _ => Ok(Type::Any),
         }
     }
 
     fn types_compatible(&self, expected: &Type, actual: &Type) -> bool {
-        if expected == &Type::Any || actual == &Type::Any {
-            return true;
+        match (expected, actual) {
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::Array(expected), Type::Array(actual)) | (Type::Map(expected), Type::Map(actual)) => {
+                self.types_compatible(expected, actual)
+            }
+            _ => expected == actual,
+        }
+    }
+
+    /// The type that values of both `a` and `b` have, where `Any` takes on
+    /// the other type; `None` when they have nothing in common.
+    fn unify(a: &Type, b: &Type) -> Option<Type> {
+        match (a, b) {
+            (Type::Any, other) | (other, Type::Any) => Some(other.clone()),
+            (Type::Array(a), Type::Array(b)) => Some(Type::Array(Box::new(Self::unify(a, b)?))),
+            (Type::Map(a), Type::Map(b)) => Some(Type::Map(Box::new(Self::unify(a, b)?))),
+            _ if a == b => Some(a.clone()),
+            _ => None,
+        }
+    }
+
+    /// The path and source of the module `path` names, or `None` when it
+    /// was already checked.
+    fn load_unvisited(&mut self, path: &str) -> Result<Option<(PathBuf, String)>, ASError> {
+        if let Some(handler) = &self.import_handler {
+            if !self.visited.insert(PathBuf::from(path)) { return Ok(None); }
+            return Ok(Some((PathBuf::from(path), handler(path)?)));
         }
-        expected == actual
+        let resolved = self.resolver.resolve(path, self.current_file.as_deref())
+            .map_err(|e| self.error(&format!("Import failed: {}", e)))?;
+        if !self.visited.insert(resolved.clone()) { return Ok(None); }
+        
+        let source = self.resolver.read_file(&resolved).map_err(|e| self.error(&format!("Read failed: {}", e)))?;
+        Ok(Some((resolved, source)))
+    }
+
+    /// Checks a module imported by name with variables and functions of its
+    /// own, then declares the requested names with their types.
+    fn check_named_import(&mut self, module: &AST, names: &[String]) -> Result<(), ASError> {
+        let importer_variables = std::mem::replace(&mut self.variables, vec![HashMap::new()]);
+        let importer_functions = std::mem::take(&mut self.functions);
+        let result = module.statements.iter().try_for_each(|statement| self.check_statement(&statement.node));
+        let module_variables = std::mem::replace(&mut self.variables, importer_variables).swap_remove(0);
+        let module_functions = std::mem::replace(&mut self.functions, importer_functions);
+        result?;
+        
+        for name in names {
+            if let Some(ty) = module_variables.get(name) {
+                self.declare_variable(name, ty.clone());
+            } else if let Some(signature) = module_functions.get(name) {
+                self.functions.insert(name.clone(), signature.clone());
+            } else {
+                // The runtime reports names the module doesn't define
+                self.declare_variable(name, Type::Any);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks `body` in a scope of its own, so its declarations end with it.
+    fn check_block(&mut self, body: &Block) -> Result<(), ASError> {
+        self.variables.push(HashMap::new());
+        for stmt in body {
+            self.span = Some(stmt.span.clone());
+            self.check_statement(&stmt.node)?;
+        }
+        self.variables.pop();
+        Ok(())
+    }
+
+    fn declare_variable(&mut self, name: &str, ty: Type) {
+        self.record(name, &ty);
+        self.variables.last_mut().unwrap().insert(name.to_string(), ty);
+    }
+
+    fn record(&mut self, name: &str, ty: &Type) {
+        if let (true, Some(span)) = (self.record_declarations, &self.span) {
+            self.declarations.push(Declaration { name: name.to_string(), ty: ty.clone(), span: span.clone() });
+        }
+    }
+
+    /// The type of the innermost visible variable called `name`.
+    fn lookup_variable(&self, name: &str) -> Option<&Type> {
+        self.variables.iter().rev().find_map(|scope| scope.get(name))
     }
 
+    /// A type error located at the start of the statement being checked.
     fn error(&self, msg: &str) -> ASError {
-        ASError::new(ErrorKind::TypeError, msg.to_string(), SourceLocation::new(0, 0))
+        let location = self.span.as_ref().map_or(SourceLocation::new(0, 0), |span| span.start.clone());
+        ASError::new(ErrorKind::TypeError, msg.to_string(), location)
     }
 }
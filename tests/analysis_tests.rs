@@ -0,0 +1,135 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+use aslang::analysis::{self, Completion, CompletionKind, SymbolKind};
+use aslang::error::{ErrorKind, SourceLocation};
+use std::path::PathBuf;
+
+const SOURCE: &str = "let count = 3;
+let names = [\"a\", \"b\"];
+fn greet(name: String) -> String {
+    let message = \"hi \" + name;
+    return message;
+}
+output greet(names[0]), len(names), missing;";
+
+fn type_at(line: usize, column: usize) -> Option<String> {
+    analysis::type_at(SOURCE, &SourceLocation::new(line, column)).map(|ty| ty.to_string())
+}
+
+#[test]
+fn test_hover_types_of_variables_and_functions() {
+    assert_eq!(type_at(1, 5), Some("Number".to_string()));
+    assert_eq!(type_at(7, 30), Some("Array<String>".to_string()));
+    assert_eq!(type_at(7, 14), Some("Array<String>".to_string()));
+    assert_eq!(type_at(7, 8), Some("fn(String) -> String".to_string()));
+    // Parameters and locals resolve inside the function body
+    assert_eq!(type_at(4, 28), Some("String".to_string()));
+    assert_eq!(type_at(5, 12), Some("String".to_string()));
+}
+
+#[test]
+fn test_hover_builtins_and_unknown_positions() {
+    assert_eq!(type_at(7, 25), Some("fn(Any) -> Number".to_string()));
+    assert_eq!(type_at(7, 37), None);
+    assert_eq!(type_at(1, 1), None);
+    assert_eq!(type_at(1, 13), None);
+}
+
+#[test]
+fn test_hover_uses_latest_declaration() {
+    let source = "let x = 1;\noutput x;\nlet x = \"s\";\noutput x;";
+    let at = |line| analysis::type_at(source, &SourceLocation::new(line, 8)).map(|ty| ty.to_string());
+    
+    assert_eq!(at(2), Some("Number".to_string()));
+    assert_eq!(at(4), Some("String".to_string()));
+}
+
+fn completion_labels(source: &str, line: usize, column: usize, kind: CompletionKind) -> Vec<String> {
+    analysis::completions(source, &SourceLocation::new(line, column)).into_iter()
+        .filter(|item| item.kind == kind)
+        .map(|item| item.label)
+        .collect()
+}
+
+#[test]
+fn test_completions_include_keywords_and_earlier_names() {
+    let items = analysis::completions(SOURCE, &SourceLocation::new(7, 1));
+    assert!(items.contains(&Completion::new("while", CompletionKind::Keyword)));
+    
+    assert_eq!(completion_labels(SOURCE, 7, 1, CompletionKind::Variable), vec!["count", "names"]);
+    assert_eq!(completion_labels(SOURCE, 7, 1, CompletionKind::Function), vec!["greet"]);
+    // Only what is declared before the cursor
+    assert_eq!(completion_labels(SOURCE, 2, 1, CompletionKind::Variable), vec!["count"]);
+}
+
+#[test]
+fn test_completions_inside_function_see_its_locals() {
+    assert_eq!(completion_labels(SOURCE, 5, 5, CompletionKind::Variable), vec!["count", "names", "name", "message"]);
+}
+
+#[test]
+fn test_completions_while_line_is_incomplete() {
+    let source = "let total = 1;\nfn add(a, b) { return a + b; }\nlet next = ";
+    assert_eq!(completion_labels(source, 3, 12, CompletionKind::Variable), vec!["total"]);
+    assert_eq!(completion_labels(source, 3, 12, CompletionKind::Function), vec!["add"]);
+}
+
+#[test]
+fn test_definition_points_at_declaring_statement() {
+    let definition = |line, column| analysis::definition_at(SOURCE, &SourceLocation::new(line, column))
+        .map(|span| (span.start.line, span.start.column, span.end.line));
+    
+    assert_eq!(definition(7, 8), Some((3, 1, 6)));
+    assert_eq!(definition(7, 14), Some((2, 1, 2)));
+    assert_eq!(definition(5, 12), Some((4, 5, 4)));
+    // Built-ins and undefined names have no declaration in the document
+    assert_eq!(definition(7, 25), None);
+    assert_eq!(definition(7, 37), None);
+}
+
+#[test]
+fn test_document_symbols_nest_function_locals() {
+    let source = "let limit = 10;\nfn count(n) {\n    let i = 0;\n    while i < n { let step = 1; i = i + step; }\n    return i;\n}\noutput count(limit);";
+    let symbols = analysis::document_symbols(source);
+    let names = |symbols: &[analysis::DocumentSymbol]| symbols.iter().map(|symbol| symbol.name.clone()).collect::<Vec<_>>();
+    
+    assert_eq!(names(&symbols), vec!["limit", "count"]);
+    assert_eq!(symbols[1].kind, SymbolKind::Function);
+    assert_eq!((symbols[1].span.start.line, symbols[1].span.end.line), (2, 6));
+    assert_eq!(names(&symbols[1].children), vec!["i", "step"]);
+    assert_eq!(symbols[1].children[1].span.start.line, 4);
+    assert!(analysis::document_symbols("fn (").is_empty());
+}
+
+#[test]
+fn test_diagnostics_report_type_errors_with_location() {
+    let errors = analysis::diagnostics("let x = 1;\n\n  let y: String = x + 1;", None);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ErrorKind::TypeError);
+    assert_eq!(errors[0].message, "Type mismatch: expected String, got Number");
+    assert_eq!(errors[0].location, SourceLocation::new(3, 3));
+    
+    assert!(analysis::diagnostics("let x = 1;", None).is_empty());
+}
+
+#[test]
+fn test_diagnostics_skip_type_check_when_parse_fails() {
+    let errors = analysis::diagnostics("let y: String = 1;\nlet = ;", None);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ErrorKind::SyntaxError);
+}
+
+#[test]
+fn test_diagnostics_report_all_parse_errors() {
+    let errors = analysis::diagnostics("let = 1;\noutput 1;\nlet y = ;", None);
+    assert_eq!(errors.iter().map(|e| e.location.line).collect::<Vec<_>>(), vec![1, 3]);
+}
+
+#[test]
+fn test_file_path_decodes_percent_escapes() {
+    assert_eq!(analysis::file_path("file:///home/ada/my%20project/main.as"), Some(PathBuf::from("/home/ada/my project/main.as")));
+    assert_eq!(analysis::file_path("file:///tmp/caf%C3%A9/100%25.as"), Some(PathBuf::from("/tmp/café/100%.as")));
+    assert_eq!(analysis::file_path("file:///tmp/50%off.as"), Some(PathBuf::from("/tmp/50%off.as")));
+    assert_eq!(analysis::file_path("file:///tmp/%FF.as"), None);
+    assert_eq!(analysis::file_path("untitled:Untitled-1"), None);
+}
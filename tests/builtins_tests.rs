@@ -0,0 +1,245 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+use aslang::runtime::builtins;
+use aslang::runtime::Value;
+use std::collections::BTreeMap;
+
+fn call(name: &str, args: &[Value]) -> Result<Value, String> {
+    builtins::lookup(name).expect("unknown built-in")(args)
+}
+
+fn sample_map() -> Value {
+    let mut entries = BTreeMap::new();
+    entries.insert("b".to_string(), Value::Number(2.0));
+    entries.insert("a".to_string(), Value::Number(1.0));
+    Value::Map(entries)
+}
+
+#[test]
+fn test_keys() {
+    let result = call("keys", &[sample_map()]).unwrap();
+    
+    assert_eq!(result, Value::Array(vec![
+        Value::String("a".to_string()),
+        Value::String("b".to_string()),
+    ]));
+}
+
+#[test]
+fn test_values() {
+    let result = call("values", &[sample_map()]).unwrap();
+    
+    assert_eq!(result, Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]));
+}
+
+#[test]
+fn test_entries() {
+    let result = call("entries", &[sample_map()]).unwrap();
+    
+    assert_eq!(result.to_string(), "[[a, 1], [b, 2]]");
+}
+
+#[test]
+fn test_keys_rejects_non_map() {
+    let err = call("keys", &[Value::Number(1.0)]).unwrap_err();
+    
+    assert_eq!(err, "keys() expects a map, got 1");
+}
+
+#[test]
+fn test_has_key() {
+    let present = call("has_key", &[sample_map(), Value::String("a".to_string())]).unwrap();
+    let absent = call("has_key", &[sample_map(), Value::String("z".to_string())]).unwrap();
+    
+    assert_eq!(present, Value::Boolean(true));
+    assert_eq!(absent, Value::Boolean(false));
+}
+
+#[test]
+fn test_remove() {
+    let removed = call("remove", &[sample_map(), Value::String("a".to_string())]).unwrap();
+    let unchanged = call("remove", &[sample_map(), Value::String("z".to_string())]).unwrap();
+    
+    assert_eq!(removed.to_string(), "{b: 2}");
+    assert_eq!(unchanged, sample_map());
+}
+
+#[test]
+fn test_get_with_default() {
+    let present = call("get", &[sample_map(), Value::String("b".to_string()), Value::None]).unwrap();
+    let absent = call("get", &[sample_map(), Value::String("z".to_string()), Value::Number(0.0)]).unwrap();
+    
+    assert_eq!(present, Value::Number(2.0));
+    assert_eq!(absent, Value::Number(0.0));
+}
+
+#[test]
+fn test_base64_round_trip() {
+    let encoded = call("base64_encode", &[Value::String("hello!?".to_string())]).unwrap();
+    assert_eq!(encoded, Value::String("aGVsbG8hPw==".to_string()));
+    
+    let decoded = call("base64_decode", &[encoded]).unwrap();
+    assert_eq!(decoded, Value::Bytes(b"hello!?".to_vec()));
+    assert_eq!(call("base64_encode", &[decoded]).unwrap(), Value::String("aGVsbG8hPw==".to_string()));
+}
+
+#[test]
+fn test_base64_decode_rejects_invalid_input() {
+    let err = call("base64_decode", &[Value::String("ab$d".to_string())]).unwrap_err();
+    assert_eq!(err, "base64_decode() got invalid character '$'");
+}
+
+#[test]
+fn test_bytes_display_is_hex_summary() {
+    assert_eq!(Value::Bytes(vec![0, 15, 255]).to_string(), "<bytes 3: 000fff>");
+    assert_eq!(Value::Bytes(vec![1; 20]).to_string(), format!("<bytes 20: {}...>", "01".repeat(16)));
+}
+
+#[test]
+fn test_format_plain_placeholders() {
+    let args = [Value::String("{} + {} = {{{}}}".to_string()), Value::Number(1.0), Value::String("x".to_string()), Value::Number(2.5)];
+    assert_eq!(call("format", &args).unwrap(), Value::String("1 + x = {2.5}".to_string()));
+}
+
+#[test]
+fn test_format_precision() {
+    let args = [Value::String("{:.2}|{:.0}".to_string()), Value::Number(1.23456), Value::Number(1.75)];
+    assert_eq!(call("format", &args).unwrap(), Value::String("1.23|2".to_string()));
+}
+
+#[test]
+fn test_format_argument_count_mismatch() {
+    let too_few = [Value::String("{} {}".to_string()), Value::Number(1.0)];
+    assert_eq!(call("format", &too_few).unwrap_err(), "format() has more placeholders than arguments (1 given)");
+    let too_many = [Value::String("{}".to_string()), Value::Number(1.0), Value::Number(2.0)];
+    assert_eq!(call("format", &too_many).unwrap_err(), "format() has more arguments (2) than placeholders");
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn person_schema() -> Value {
+    let mut address = BTreeMap::new();
+    address.insert("city".to_string(), string("string"));
+    let mut schema = BTreeMap::new();
+    schema.insert("name".to_string(), string("string"));
+    schema.insert("age".to_string(), string("number"));
+    schema.insert("address".to_string(), Value::Map(address));
+    Value::Map(schema)
+}
+
+fn person(age: Value, city: Option<Value>) -> Value {
+    let mut address = BTreeMap::new();
+    if let Some(city) = city {
+        address.insert("city".to_string(), city);
+    }
+    let mut entries = BTreeMap::new();
+    entries.insert("name".to_string(), string("Ada"));
+    entries.insert("age".to_string(), age);
+    entries.insert("address".to_string(), Value::Map(address));
+    Value::Map(entries)
+}
+
+#[test]
+fn test_typeof() {
+    assert_eq!(call("typeof", &[Value::Number(1.0)]).unwrap(), string("number"));
+    assert_eq!(call("typeof", &[sample_map()]).unwrap(), string("map"));
+    assert_eq!(call("typeof", &[Value::None]).unwrap(), string("none"));
+}
+
+#[test]
+fn test_validate_passes() {
+    let value = person(Value::Number(36.0), Some(string("London")));
+    assert_eq!(call("validate", &[value, person_schema()]).unwrap(), Value::Array(vec![]));
+}
+
+#[test]
+fn test_validate_reports_type_mismatch() {
+    let value = person(string("36"), Some(Value::Number(1.0)));
+    assert_eq!(call("validate", &[value, person_schema()]).unwrap(), Value::Array(vec![
+        string("address.city: expected string, got number"),
+        string("age: expected number, got string"),
+    ]));
+}
+
+#[test]
+fn test_validate_reports_missing_field() {
+    let value = person(Value::Number(36.0), None);
+    assert_eq!(call("validate", &[value, person_schema()]).unwrap(), Value::Array(vec![
+        string("address.city: missing field"),
+    ]));
+}
+
+#[test]
+fn test_len() {
+    assert_eq!(call("len", &[Value::Array(vec![Value::None, Value::None])]).unwrap(), Value::Integer(2));
+    assert_eq!(call("len", &[string("héllo")]).unwrap(), Value::Integer(5));
+    assert_eq!(call("len", &[sample_map()]).unwrap(), Value::Integer(2));
+    assert_eq!(
+        call("len", &[Value::Number(3.0)]).unwrap_err(),
+        "len() expects a string, array, map or bytes, got number"
+    );
+}
+
+#[test]
+fn test_math_functions() {
+    assert_eq!(call("sqrt", &[Value::Integer(16)]).unwrap(), Value::Number(4.0));
+    assert_eq!(call("abs", &[Value::Integer(-3)]).unwrap(), Value::Integer(3));
+    assert_eq!(call("abs", &[Value::Number(-2.5)]).unwrap(), Value::Number(2.5));
+    assert_eq!(call("floor", &[Value::Number(2.7)]).unwrap(), Value::Integer(2));
+    assert_eq!(call("ceil", &[Value::Number(2.1)]).unwrap(), Value::Integer(3));
+    assert_eq!(call("round", &[Value::Number(-2.5)]).unwrap(), Value::Integer(-3));
+    assert_eq!(call("min", &[Value::Integer(2), Value::Number(1.5)]).unwrap(), Value::Number(1.5));
+    assert_eq!(call("max", &[Value::Integer(2), Value::Number(1.5)]).unwrap(), Value::Integer(2));
+    assert_eq!(call("pow", &[Value::Integer(2), Value::Integer(10)]).unwrap(), Value::Number(1024.0));
+}
+
+#[test]
+fn test_math_argument_count_names_function() {
+    assert_eq!(call("sqrt", &[]).unwrap_err(), "sqrt() expects 1 argument(s), got 0");
+    assert_eq!(call("max", &[Value::Integer(1)]).unwrap_err(), "max() expects 2 argument(s), got 1");
+    assert_eq!(call("sqrt", &[Value::String("x".to_string())]).unwrap_err(), "sqrt() expects a number, got string");
+}
+
+#[test]
+fn test_case_and_trim() {
+    assert_eq!(call("upper", &[string("Straße")]).unwrap(), string("STRASSE"));
+    assert_eq!(call("lower", &[string("HeLLo")]).unwrap(), string("hello"));
+    assert_eq!(call("trim", &[string("  hi \n")]).unwrap(), string("hi"));
+    assert_eq!(call("upper", &[Value::Integer(1)]).unwrap_err(), "upper() expects a string, got number");
+}
+
+#[test]
+fn test_split_and_join() {
+    let parts = call("split", &[string("a,b,,c"), string(",")]).unwrap();
+    assert_eq!(parts, Value::Array(vec![string("a"), string("b"), string(""), string("c")]));
+    assert_eq!(call("join", &[parts, string("-")]).unwrap(), string("a-b--c"));
+    assert_eq!(call("split", &[string("abc"), string("")]).unwrap().to_string(), "[a, b, c]");
+    assert_eq!(
+        call("join", &[Value::Array(vec![string("a"), Value::Integer(1)]), string("")]).unwrap_err(),
+        "join() expects an array of strings, found number"
+    );
+}
+
+#[test]
+fn test_substring() {
+    assert_eq!(call("substring", &[string("héllo"), Value::Integer(1), Value::Integer(4)]).unwrap(), string("éll"));
+    assert_eq!(call("substring", &[string("abc"), Value::Integer(3), Value::Integer(3)]).unwrap(), string(""));
+    assert_eq!(
+        call("substring", &[string("abc"), Value::Integer(0), Value::Integer(4)]).unwrap_err(),
+        "substring() bound 4 is not an integer from 0 to 3"
+    );
+    assert_eq!(
+        call("substring", &[string("abc"), Value::Integer(2), Value::Integer(1)]).unwrap_err(),
+        "substring() start 2 is after end 1"
+    );
+}
+
+#[test]
+fn test_to_number_and_to_string() {
+    assert_eq!(call("to_number", &[string(" 42 ")]).unwrap(), Value::Integer(42));
+    assert_eq!(call("to_number", &[string("2.5")]).unwrap(), Value::Number(2.5));
+    assert_eq!(call("to_number", &[string("abc")]).unwrap_err(), "to_number() could not parse 'abc' as a number");
+    assert_eq!(call("to_string", &[Value::Number(2.5)]).unwrap(), string("2.5"));
+}
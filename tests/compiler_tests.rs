@@ -0,0 +1,237 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+use aslang::compiler::{serialize, Compiler, Constant, Opcode};
+use aslang::parser::Parser;
+use std::rc::Rc;
+
+#[test]
+fn test_function_compiled_into_code_object() {
+    let ast = Parser::parse("fn add(a, b) { return a + b; } output add(1, 2);").unwrap();
+    let mut compiler = Compiler::new();
+    let program = compiler.compile(&ast).unwrap();
+    
+    assert_eq!(program.functions.len(), 1);
+    assert_eq!(program.functions[0].name, "add");
+    assert_eq!(program.functions[0].arity, 2);
+    assert!(program.functions[0].bytecode.contains(&Opcode::Add));
+    
+    assert_eq!(program.main.bytecode, vec![
+        Opcode::LoadConst(0),
+        Opcode::LoadConst(1),
+        Opcode::CallFunction(0, 2),
+        Opcode::Output(true),
+    ]);
+    assert_eq!(program.main.constants, vec![Constant::Integer(1), Constant::Integer(2)]);
+}
+
+#[test]
+fn test_call_before_definition_uses_function_index() {
+    let ast = Parser::parse("output twice(4); fn twice(n) { return n * 2; }").unwrap();
+    let mut compiler = Compiler::new();
+    let program = compiler.compile(&ast).unwrap();
+    
+    assert!(program.main.bytecode.contains(&Opcode::CallFunction(0, 1)));
+    assert_eq!(program.functions[0].name, "twice");
+}
+
+#[test]
+fn test_same_scope_redeclaration_warns() {
+    let ast = Parser::parse("let x = 1;\nlet x = 2;").unwrap();
+    let mut compiler = Compiler::new();
+    compiler.compile(&ast).unwrap();
+    
+    assert_eq!(compiler.warnings.len(), 1);
+    let warning = &compiler.warnings[0];
+    assert_eq!(warning.message, "Variable 'x' redeclares a name already defined in this scope");
+    assert_eq!(warning.location.line, 2);
+    assert_eq!(warning.previous.as_ref().unwrap().line, 1);
+}
+
+#[test]
+fn test_inner_scope_shadow_does_not_warn() {
+    let source = "let x = 1;\nfn f(n) { let x = n; return x; }\nif true { let x = 3; }";
+    let ast = Parser::parse(source).unwrap();
+    let mut compiler = Compiler::new();
+    compiler.compile(&ast).unwrap();
+    
+    assert!(compiler.warnings.is_empty());
+}
+
+#[test]
+fn test_identical_string_literals_share_pool_entry() {
+    let ast = Parser::parse("let a = \"hi\"; let b = \"hi\"; output \"hi\", a;").unwrap();
+    let mut compiler = Compiler::new();
+    let program = compiler.compile(&ast).unwrap();
+    
+    assert_eq!(program.main.constants, vec![Constant::String(Rc::from("hi"))]);
+    assert_eq!(program.main.bytecode.iter().filter(|op| **op == Opcode::LoadConst(0)).count(), 2);
+    assert!(program.main.bytecode.contains(&Opcode::OutputStr(0, false)));
+}
+
+#[test]
+fn test_duplicate_literals_map_to_one_pool_entry() {
+    let ast = Parser::parse("output 1, 1, true, true, \"a\", 2, 1;").unwrap();
+    let mut compiler = Compiler::new();
+    let program = compiler.compile(&ast).unwrap();
+    
+    assert_eq!(program.main.constants, vec![
+        Constant::Integer(1),
+        Constant::Boolean(true),
+        Constant::String(Rc::from("a")),
+        Constant::Integer(2),
+    ]);
+    assert_eq!(program.main.bytecode.iter().filter(|op| **op == Opcode::LoadConst(0)).count(), 3);
+}
+
+#[test]
+fn test_nested_control_flow_patches_jumps_without_panicking() {
+    let sources = [
+        "if true { output 1; }",
+        "if true { output 1; } else { output 2; }",
+        "if false { output 1; } elseif true { output 2; } elseif false { output 3; } else { output 4; }",
+        "while false { if true { while false { output 1; } } elseif false { output 2; } }",
+        "fn f(n) { if n > 1 { return 1; } elseif n < 0 { return 2; } return 3; } output f(1);",
+        "if true { } elseif true { } else { }",
+    ];
+    for source in sources {
+        let ast = Parser::parse(source).unwrap();
+        let program = Compiler::new().compile(&ast);
+        assert!(program.is_ok(), "source: {}", source);
+    }
+}
+
+#[test]
+fn test_whole_literals_compile_to_integers() {
+    let ast = Parser::parse("output 3, 2.5, 9007199254740993;").unwrap();
+    let program = Compiler::new().compile(&ast).unwrap();
+    
+    assert_eq!(program.main.constants, vec![
+        Constant::Integer(3),
+        Constant::Number(2.5),
+        Constant::Number(9007199254740992.0),
+    ]);
+}
+
+#[test]
+fn test_disassemble_shows_absolute_jump_targets() {
+    let bytecode = vec![
+        Opcode::LoadConst(3),
+        Opcode::JumpIfFalse(4),
+        Opcode::LoadVar("x".to_string()),
+        Opcode::JumpRel(-3),
+        Opcode::Call("len".to_string(), 1),
+        Opcode::Pop,
+    ];
+    assert_eq!(Compiler::disassemble(&bytecode), "\
+0000 LoadConst 3
+0001 JumpIfFalse 0004
+0002 LoadVar x
+0003 JumpRel 0000
+0004 Call len 1
+0005 Pop
+");
+}
+
+#[test]
+fn test_program_serialization_round_trips() {
+    let source = "fn greet(name) { output \"hi \" + name; } let xs = [1, 2.5, 'c', true]; \
+                  if len(xs) > 3 and not false { greet(\"ada\"); } let m = {a: 1}; m[\"b\"] = 2;";
+    let ast = Parser::parse(source).unwrap();
+    let mut compiler = Compiler::new();
+    compiler.relative_jumps = true;
+    let program = compiler.compile(&ast).unwrap();
+    
+    let bytes = serialize::serialize_program(&program);
+    assert!(bytes.starts_with(serialize::MAGIC));
+    assert_eq!(serialize::deserialize_program(&bytes).unwrap(), program);
+}
+
+#[test]
+fn test_opcode_serialization_round_trips() {
+    let bytecode = vec![
+        Opcode::Call("len".to_string(), 1),
+        Opcode::JumpIfFalseRel(-4),
+        Opcode::OutputStr(2, false),
+        Opcode::Input(true),
+        Opcode::MakeMap(3),
+    ];
+    assert_eq!(serialize::deserialize(&serialize::serialize(&bytecode)).unwrap(), bytecode);
+}
+
+#[test]
+fn test_deserialize_rejects_bad_header_and_version() {
+    let mut bytes = serialize::serialize(&[Opcode::Pop]);
+    bytes[serialize::MAGIC.len()] = serialize::FORMAT_VERSION + 1;
+    let err = serialize::deserialize(&bytes).unwrap_err();
+    assert_eq!(err.message, format!(
+        "Invalid bytecode: format version {} is not supported (expected {})",
+        serialize::FORMAT_VERSION + 1, serialize::FORMAT_VERSION
+    ));
+    
+    let err = serialize::deserialize(b"PK\x03\x04").unwrap_err();
+    assert_eq!(err.message, "Invalid bytecode: missing file header");
+    let bytes = serialize::serialize(&[Opcode::LoadVar("x".to_string())]);
+    let err = serialize::deserialize(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert_eq!(err.message, "Invalid bytecode: unexpected end of data");
+}
+
+fn compile_optimized(source: &str) -> (Vec<Opcode>, Vec<Constant>) {
+    let ast = Parser::parse(source).unwrap();
+    let mut compiler = Compiler::new();
+    compiler.optimize = true;
+    let program = compiler.compile(&ast).unwrap();
+    (program.main.bytecode, program.main.constants)
+}
+
+#[test]
+fn test_constant_folding_emits_single_load() {
+    let source = "output 2 + 3 * 4;";
+    let unoptimized = Compiler::new().compile(&Parser::parse(source).unwrap()).unwrap();
+    let (bytecode, constants) = compile_optimized(source);
+    
+    assert!(bytecode.len() < unoptimized.main.bytecode.len());
+    assert_eq!(bytecode, vec![Opcode::LoadConst(0), Opcode::Output(true)]);
+    assert_eq!(constants, vec![Constant::Integer(14)]);
+    
+    let (_, constants) = compile_optimized("output -(7 / 2), \"a\" + \"b\", not (1 < 2);");
+    assert_eq!(constants, vec![
+        Constant::Number(-3.5),
+        Constant::String(Rc::from("ab")),
+        Constant::Boolean(false),
+    ]);
+}
+
+#[test]
+fn test_constant_folding_keeps_division_by_zero() {
+    let (bytecode, _) = compile_optimized("output 1 / 0;");
+    assert!(bytecode.contains(&Opcode::Divide));
+    let (bytecode, _) = compile_optimized("output 5 % (2 - 2);");
+    assert!(bytecode.contains(&Opcode::Modulo));
+    assert!(!bytecode.contains(&Opcode::Subtract));
+}
+
+#[test]
+fn test_block_and_function_variables_use_slots() {
+    let ast = Parser::parse("let x = 1; if true { let x = 2; output x; } fn f(a) { let b = a; return b; }").unwrap();
+    let program = Compiler::new().compile(&ast).unwrap();
+    
+    assert!(program.main.bytecode.contains(&Opcode::StoreVar("x".to_string())));
+    assert!(program.main.bytecode.contains(&Opcode::StoreLocal(0)));
+    assert!(program.main.bytecode.contains(&Opcode::LoadLocal(0)));
+    let function = &program.functions[0].bytecode;
+    assert!(function.contains(&Opcode::LoadLocal(0)));
+    assert!(function.contains(&Opcode::StoreLocal(1)));
+    assert!(!function.iter().any(|op| matches!(op, Opcode::LoadVar(_) | Opcode::StoreVar(_))));
+}
+
+#[test]
+fn test_named_import_compiles_at_top_level_only() {
+    let program = Compiler::new().compile(&Parser::parse("import { add } from \"math\"; output add;").unwrap()).unwrap();
+    assert_eq!(program.main.bytecode[0], Opcode::ImportNames("math".to_string(), vec!["add".to_string()]));
+    assert_eq!(program.main.bytecode[1], Opcode::LoadVar("add".to_string()));
+    assert_eq!(serialize::deserialize(&serialize::serialize(&program.main.bytecode)).unwrap(), program.main.bytecode);
+    
+    let err = Compiler::new().compile(&Parser::parse("if true { import { add } from \"math\"; }").unwrap()).unwrap_err();
+    assert_eq!(err.message, "Named imports are only allowed at the top level");
+    assert_eq!((err.location.line, err.location.column), (1, 11));
+}
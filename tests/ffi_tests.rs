@@ -0,0 +1,173 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+use aslang::ffi::*;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+/// Reads a (possibly null) C string returned by the library.
+fn read(s: *mut c_char) -> Option<String> {
+    if s.is_null() {
+        return None;
+    }
+    Some(unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string())
+}
+
+/// What `f` writes to the process's standard output file descriptor, which
+/// the test harness's capturing of `print!` doesn't cover.
+fn captured_stdout(f: impl FnOnce()) -> String {
+    use std::io::{Read, Seek, Write};
+    use std::os::fd::AsRawFd;
+    // Tests run in parallel, and only one can own the descriptor at a time
+    static STDOUT: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let _guard = STDOUT.lock().unwrap_or_else(|e| e.into_inner());
+    
+    let path = std::env::temp_dir().join(format!("aslang_ffi_stdout_{}", std::process::id()));
+    let mut file = std::fs::OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    std::io::stdout().flush().unwrap();
+    let saved = unsafe { libc::dup(1) };
+    unsafe { libc::dup2(file.as_raw_fd(), 1) };
+    f();
+    std::io::stdout().flush().unwrap();
+    unsafe {
+        libc::dup2(saved, 1);
+        libc::close(saved);
+    }
+    let mut text = String::new();
+    file.rewind().unwrap();
+    file.read_to_string(&mut text).unwrap();
+    text
+}
+
+fn execute_result(code: &str) -> (i32, Option<String>, Option<String>) {
+    let code = CString::new(code).unwrap();
+    let result = unsafe { as_execute_result(code.as_ptr()) };
+    let parts = (result.status, read(result.output), read(result.message));
+    unsafe { as_free_result(result) };
+    parts
+}
+
+#[test]
+fn test_execute_result_separates_output_from_errors() {
+    assert_eq!(execute_result("output 1 + 2;"), (AS_OK, Some("3\n".to_string()), None));
+    
+    let (status, output, message) = execute_result("output 1 / 0;");
+    assert_eq!(status, status_code(&aslang::error::ErrorKind::RuntimeError));
+    assert_eq!(output, None);
+    assert!(message.unwrap().contains("Division by zero"));
+    
+    let (status, _, _) = execute_result("let = 1;");
+    assert_eq!(status, status_code(&aslang::error::ErrorKind::SyntaxError));
+}
+
+#[test]
+fn test_execute_result_does_not_print_output() {
+    let stdout = captured_stdout(|| {
+        assert_eq!(execute_result("output \"result only\";").1, Some("result only\n".to_string()));
+    });
+    assert!(!stdout.contains("result only"), "printed {:?}", stdout);
+}
+
+#[test]
+fn test_execute_result_rejects_null_code() {
+    let result = unsafe { as_execute_result(std::ptr::null()) };
+    assert_eq!(result.status, AS_INVALID_INPUT);
+    assert_eq!(read(result.message), Some("Code is null".to_string()));
+    unsafe { as_free_result(result) };
+}
+
+#[test]
+fn test_runtime_handle_keeps_state_between_calls() {
+    let runtime = as_runtime_new();
+    let run = |code: &str| {
+        let code = CString::new(code).unwrap();
+        let output = unsafe { as_runtime_execute(runtime, code.as_ptr()) };
+        let text = read(output);
+        as_free_string(output);
+        text.unwrap()
+    };
+    
+    assert_eq!(run("let total = 40; fn twice(n) { return n * 2; }"), "");
+    assert_eq!(run("total = total + 2; output total, twice(total);"), "42 84\n");
+    assert!(run("output missing;").starts_with("Error: "));
+    assert_eq!(run("output total;"), "42\n");
+    unsafe { as_runtime_free(runtime) };
+}
+
+#[test]
+fn test_runtime_handle_does_not_print_output_or_prompts() {
+    let runtime = as_runtime_new();
+    let stdout = captured_stdout(|| unsafe {
+        as_set_input_callback(runtime, Some(answer_prompt));
+        let code = CString::new("input \"Name? \" name; output \"handle only\";").unwrap();
+        let output = as_runtime_execute(runtime, code.as_ptr());
+        assert_eq!(read(output), Some("Name? handle only\n".to_string()));
+        as_free_string(output);
+    });
+    assert!(!stdout.contains("handle only") && !stdout.contains("Name?"), "printed {:?}", stdout);
+    unsafe { as_runtime_free(runtime) };
+}
+
+#[test]
+fn test_runtime_handle_guards_against_null() {
+    let code = CString::new("output 1;").unwrap();
+    assert!(unsafe { as_runtime_execute(std::ptr::null_mut(), code.as_ptr()) }.is_null());
+    
+    let runtime = as_runtime_new();
+    assert!(unsafe { as_runtime_execute(runtime, std::ptr::null()) }.is_null());
+    unsafe {
+        as_runtime_free(runtime);
+        as_runtime_free(std::ptr::null_mut());
+    }
+}
+
+extern "C" fn answer_prompt(prompt: *const c_char) -> *mut c_char {
+    let prompt = unsafe { CStr::from_ptr(prompt) }.to_str().unwrap();
+    let line: &'static CStr = match prompt {
+        "Name? " => c"Ada",
+        "" => c"42",
+        _ => return std::ptr::null_mut(),
+    };
+    line.as_ptr() as *mut c_char
+}
+
+#[test]
+fn test_input_callback_supplies_lines() {
+    let runtime = as_runtime_new();
+    unsafe { as_set_input_callback(runtime, Some(answer_prompt)) };
+    let run = |code: &str| {
+        let code = CString::new(code).unwrap();
+        let output = unsafe { as_runtime_execute(runtime, code.as_ptr()) };
+        let text = read(output);
+        as_free_string(output);
+        text.unwrap()
+    };
+    
+    assert_eq!(run("input \"Name? \" name; input n; output name, n;"), "Name? Ada 42\n");
+    assert!(run("input \"Age? \" age;").contains("end of input"));
+    unsafe {
+        as_set_input_callback(std::ptr::null_mut(), Some(answer_prompt));
+        as_runtime_free(runtime);
+    }
+}
+
+#[test]
+fn test_runtime_handle_instruction_limit() {
+    let runtime = as_runtime_new();
+    let run = |code: &str| {
+        let code = CString::new(code).unwrap();
+        let output = unsafe { as_runtime_execute(runtime, code.as_ptr()) };
+        let text = read(output);
+        as_free_string(output);
+        text.unwrap()
+    };
+    
+    unsafe { as_runtime_set_instruction_limit(runtime, 500) };
+    assert_eq!(run("while true { }"), "Error: [1:1] Runtime Error: Instruction budget exceeded: executed 500 opcodes");
+    unsafe { as_runtime_set_instruction_limit(runtime, 0) };
+    assert_eq!(run("let i = 0; while i < 500 { i = i + 1; } output i;"), "500\n");
+    unsafe {
+        as_runtime_set_instruction_limit(std::ptr::null_mut(), 1);
+        as_runtime_free(runtime);
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
 
 use aslang::lexer::{Lexer, Token};
+use aslang::error::SourceLocation;
 
 #[test]
 fn test_basic_arithmetic() {
@@ -45,7 +46,7 @@ fn test_string_literals() {
     let tokens = lexer.tokenize().unwrap();
     
     assert_eq!(tokens[0], Token::String("Hello World".to_string()));
-    // Note: Our simple lexer doesn't handle escapes yet, so we just test basic string
+    assert_eq!(tokens[1], Token::String("Use \"quotes\"".to_string()));
 }
 
 #[test]
@@ -61,3 +62,271 @@ fn test_operators() {
     assert_eq!(tokens[4], Token::And);
     assert_eq!(tokens[5], Token::Or);
 }
+
+#[test]
+fn test_compound_assignment_operators() {
+    let tokens = Lexer::new("+= -= *= /= ++ --").tokenize().unwrap();
+    assert_eq!(&tokens[..6], &[Token::PlusEq, Token::MinusEq, Token::StarEq, Token::SlashEq, Token::Inc, Token::Dec]);
+}
+
+#[test]
+fn test_angle_brackets_without_spaces() {
+    let tokens = Lexer::new("a<b>c").tokenize().unwrap();
+    assert_eq!(tokens[1], Token::Lt);
+    assert_eq!(tokens[2], Token::Identifier("b".to_string()));
+    assert_eq!(tokens[3], Token::Gt);
+    assert_eq!(tokens[4], Token::Identifier("c".to_string()));
+}
+
+#[test]
+fn test_token_spans() {
+    let input = "let x = 1;\n  output x;";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize_spanned().unwrap();
+    
+    let (token, span) = &tokens[5];
+    assert_eq!(*token, Token::Output);
+    assert_eq!((span.start.line, span.start.column), (2, 3));
+    assert_eq!((span.end.line, span.end.column), (2, 9));
+}
+
+#[test]
+fn test_leading_dot_float() {
+    let mut lexer = Lexer::new(".5 + 3.");
+    let tokens = lexer.tokenize().unwrap();
+    
+    assert_eq!(tokens, vec![Token::Number(0.5), Token::Plus, Token::Number(3.0), Token::EOF]);
+}
+
+#[test]
+fn test_bare_dot_is_not_a_number() {
+    let mut lexer = Lexer::new("a . b .. c");
+    let tokens = lexer.tokenize().unwrap();
+    
+    assert_eq!(tokens[1], Token::Dot);
+    assert_eq!(tokens[3], Token::DotDot);
+}
+
+#[test]
+fn test_trailing_dot_float() {
+    let mut lexer = Lexer::new("3.");
+    let tokens = lexer.tokenize().unwrap();
+    
+    assert_eq!(tokens, vec![Token::Number(3.0), Token::EOF]);
+}
+
+#[test]
+fn test_number_followed_by_range() {
+    let mut lexer = Lexer::new("3..4");
+    let tokens = lexer.tokenize().unwrap();
+    
+    assert_eq!(tokens, vec![Token::Number(3.0), Token::DotDot, Token::Number(4.0), Token::EOF]);
+}
+
+#[test]
+fn test_number_followed_by_member_access() {
+    let mut lexer = Lexer::new("3.4.x");
+    let tokens = lexer.tokenize().unwrap();
+    
+    assert_eq!(tokens, vec![
+        Token::Number(3.4),
+        Token::Dot,
+        Token::Identifier("x".to_string()),
+        Token::EOF,
+    ]);
+}
+
+#[test]
+fn test_malformed_double_dot_number() {
+    let mut lexer = Lexer::new("let x = 3.4.5;");
+    let err = lexer.tokenize().unwrap_err();
+    
+    assert_eq!(err.message, "Invalid number: 3.4.5");
+    assert_eq!((err.location.line, err.location.column), (1, 9));
+}
+
+#[test]
+fn test_radix_integer_literals() {
+    let mut lexer = Lexer::new("0xFF 0b1010 0o17 0Xff 0 07");
+    let tokens = lexer.tokenize().unwrap();
+    
+    assert_eq!(tokens, vec![
+        Token::Number(255.0),
+        Token::Number(10.0),
+        Token::Number(15.0),
+        Token::Number(255.0),
+        Token::Number(0.0),
+        Token::Number(7.0),
+        Token::EOF,
+    ]);
+}
+
+#[test]
+fn test_malformed_radix_literals() {
+    for (source, message) in [("0x", "Invalid number: 0x"), ("0b2", "Invalid number: 0b2"), ("0o8;", "Invalid number: 0o8"), ("0xfg", "Invalid number: 0xfg")] {
+        let mut lexer = Lexer::new(source);
+        let err = lexer.tokenize().unwrap_err();
+        assert_eq!(err.message, message, "source: {}", source);
+    }
+}
+
+#[test]
+fn test_scientific_notation_literals() {
+    let mut lexer = Lexer::new("1e10 6.022e23 1.5E-3 2e+2 .5e1");
+    let tokens = lexer.tokenize().unwrap();
+    
+    assert_eq!(tokens, vec![
+        Token::Number(1e10),
+        Token::Number(6.022e23),
+        Token::Number(1.5e-3),
+        Token::Number(200.0),
+        Token::Number(5.0),
+        Token::EOF,
+    ]);
+}
+
+#[test]
+fn test_exponent_without_digits_is_error() {
+    for (source, message) in [("1e", "Invalid number: 1e"), ("1e+;", "Invalid number: 1e+"), ("2E-x", "Invalid number: 2E-")] {
+        let mut lexer = Lexer::new(source);
+        let err = lexer.tokenize().unwrap_err();
+        assert_eq!(err.message, message, "source: {}", source);
+    }
+}
+
+#[test]
+fn test_identifier_e_is_not_absorbed() {
+    let mut lexer = Lexer::new("1 + e");
+    let tokens = lexer.tokenize().unwrap();
+    
+    assert_eq!(tokens, vec![
+        Token::Number(1.0),
+        Token::Plus,
+        Token::Identifier("e".to_string()),
+        Token::EOF,
+    ]);
+}
+
+#[test]
+fn test_digit_separators() {
+    let mut lexer = Lexer::new("1_000_000 1.234_567 0xFF_FF 1_0e1_0 _x1");
+    let tokens = lexer.tokenize().unwrap();
+    
+    assert_eq!(tokens, vec![
+        Token::Number(1_000_000.0),
+        Token::Number(1.234_567),
+        Token::Number(65535.0),
+        Token::Number(10e10),
+        Token::Identifier("_x1".to_string()),
+        Token::EOF,
+    ]);
+}
+
+#[test]
+fn test_misplaced_digit_separators() {
+    for (source, message) in [("100_;", "Invalid number: 100_"), ("1__0", "Invalid number: 1__0"), ("0x_F", "Invalid number: 0x_F")] {
+        let mut lexer = Lexer::new(source);
+        let err = lexer.tokenize().unwrap_err();
+        assert_eq!(err.message, message, "source: {}", source);
+    }
+    // A leading underscore starts a name; the type checker rejects it as a number if unbound
+    assert_eq!(Lexer::new("_100").tokenize().unwrap(), vec![Token::Identifier("_100".to_string()), Token::EOF]);
+}
+
+#[test]
+fn test_nested_block_comments() {
+    let mut lexer = Lexer::new("1 /* a /* b */ c */ 2 /* multi\nline */ 3");
+    let tokens = lexer.tokenize_spanned().unwrap();
+    let values: Vec<Token> = tokens.iter().map(|(token, _)| token.clone()).collect();
+    
+    assert_eq!(values, vec![Token::Number(1.0), Token::Number(2.0), Token::Number(3.0), Token::EOF]);
+    assert_eq!((tokens[2].1.start.line, tokens[2].1.start.column), (2, 9));
+}
+
+#[test]
+fn test_unterminated_block_comment() {
+    let mut lexer = Lexer::new("let x = 1;\n  /* open /* nested */");
+    let err = lexer.tokenize().unwrap_err();
+    
+    assert_eq!(err.message, "Unterminated block comment");
+    assert_eq!((err.location.line, err.location.column), (2, 3));
+}
+
+#[test]
+fn test_char_literals() {
+    let mut lexer = Lexer::new(r"'a' '\n' '\'' '\\'");
+    let tokens = lexer.tokenize().unwrap();
+    
+    assert_eq!(tokens, vec![
+        Token::Char('a'),
+        Token::Char('\n'),
+        Token::Char('\''),
+        Token::Char('\\'),
+        Token::EOF,
+    ]);
+}
+
+#[test]
+fn test_invalid_char_literals() {
+    for (source, message) in [
+        ("''", "Empty character literal"),
+        ("'ab'", "Character literal must contain exactly one character"),
+        ("'a", "Unterminated character literal"),
+    ] {
+        let mut lexer = Lexer::new(source);
+        let err = lexer.tokenize().unwrap_err();
+        assert_eq!(err.message, message, "source: {}", source);
+    }
+}
+
+#[test]
+fn test_arrow_token() {
+    let tokens = Lexer::new(") -> Number - 1").tokenize().unwrap();
+    assert_eq!(&tokens[..5], &[Token::RParen, Token::Arrow, Token::Identifier("Number".to_string()), Token::Minus, Token::Number(1.0)]);
+}
+
+#[test]
+fn test_tokenize_partial_keeps_tokens_before_error() {
+    let (tokens, error) = Lexer::new("let x = 1;\nlet y = @;").tokenize_partial();
+    let kinds: Vec<Token> = tokens.iter().map(|(token, _)| token.clone()).collect();
+    
+    assert_eq!(kinds.len(), 8);
+    assert_eq!(kinds[5], Token::Let);
+    assert_eq!(kinds[7], Token::Eq);
+    assert_eq!(tokens[7].1.start, SourceLocation::new(2, 7));
+    let error = error.unwrap();
+    assert_eq!(error.message, "Unexpected character: @");
+    assert_eq!(error.location, SourceLocation::new(2, 9));
+    
+    let (tokens, error) = Lexer::new("output 1;").tokenize_partial();
+    assert!(error.is_none());
+    assert_eq!(tokens.last().unwrap().0, Token::EOF);
+}
+
+#[test]
+fn test_operator_at_end_of_input() {
+    for (source, last) in [("x =", Token::Eq), ("x +", Token::Plus), ("x !", Token::Not), ("x |", Token::BitwiseOr)] {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        assert_eq!(tokens, vec![Token::Identifier("x".to_string()), last, Token::EOF], "source: {}", source);
+    }
+}
+
+#[test]
+fn test_token_kind_names_omit_payload() {
+    let tokens = Lexer::new("let x = 'a' + \"${x}\" >= 2; // done").tokenize().unwrap();
+    let kinds: Vec<&str> = tokens.iter().map(Token::kind_name).collect();
+    assert_eq!(kinds, vec!["Let", "Identifier", "Eq", "Char", "Plus", "InterpolatedString", "Ge", "Number", "Semicolon", "EOF"]);
+}
+
+#[test]
+fn test_string_escapes() {
+    let tokens = Lexer::new(r#""a\tb\n" "\\ \$ \0" "C:\dir\new" "\q""#).tokenize().unwrap();
+    assert_eq!(tokens[0], Token::String("a\tb\n".to_string()));
+    assert_eq!(tokens[1], Token::String("\\ $ \0".to_string()));
+    // An unknown escape keeps its backslash; a known one still applies
+    assert_eq!(tokens[2], Token::String("C:\\dir\new".to_string()));
+    assert_eq!(tokens[3], Token::String("\\q".to_string()));
+    
+    let err = Lexer::new("\"open\\").tokenize().unwrap_err();
+    assert_eq!(err.message, "Unterminated escape sequence");
+}
@@ -0,0 +1,228 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+use aslang::parser::{Expression, Parser, Statement};
+use aslang::error::ErrorKind;
+use aslang::types::Type;
+
+#[test]
+fn test_statement_spans() {
+    let spans = Parser::statement_spans("let x = 1;\n  output x + 2;").unwrap();
+    
+    assert_eq!(spans.len(), 2);
+    assert_eq!((spans[0].start.line, spans[0].start.column), (1, 1));
+    assert_eq!((spans[0].end.line, spans[0].end.column), (1, 11));
+    assert_eq!((spans[1].start.line, spans[1].start.column), (2, 3));
+    assert_eq!((spans[1].end.line, spans[1].end.column), (2, 16));
+}
+
+#[test]
+fn test_statement_span_covers_multiline_block() {
+    let ast = Parser::parse("if true {\n  output 1;\n}\noutput 2;").unwrap();
+    let spans = ast.statement_spans();
+    
+    assert_eq!((spans[0].start.line, spans[0].end.line), (1, 3));
+    assert_eq!((spans[1].start.line, spans[1].end.line), (4, 4));
+}
+
+fn parse_if(source: &str) -> Statement {
+    let mut ast = Parser::parse(source).unwrap();
+    assert_eq!(ast.statements.len(), 1, "source: {}", source);
+    ast.statements.remove(0).node
+}
+
+#[test]
+fn test_braceless_if() {
+    let Statement::If { then_branch, else_branch, .. } = parse_if("if x > 0 output x;") else {
+        panic!("expected if");
+    };
+    assert_eq!(then_branch.len(), 1);
+    assert!(matches!(then_branch[0].node, Statement::Output(_)));
+    assert!(else_branch.is_none());
+}
+
+#[test]
+fn test_braceless_if_else() {
+    let Statement::If { then_branch, elif_branches, else_branch, .. } =
+        parse_if("if x > 0 output 1; elseif x < 0 output 2; else output 3;") else {
+        panic!("expected if");
+    };
+    assert_eq!(then_branch.len(), 1);
+    assert_eq!(elif_branches.len(), 1);
+    assert_eq!(else_branch.unwrap().len(), 1);
+}
+
+#[test]
+fn test_dangling_else_binds_to_nearest_if() {
+    let Statement::If { then_branch, else_branch, .. } = parse_if("if a if b output 1; else output 2;") else {
+        panic!("expected if");
+    };
+    assert!(else_branch.is_none());
+    let Statement::If { else_branch: inner_else, .. } = &then_branch[0].node else {
+        panic!("expected nested if");
+    };
+    assert!(inner_else.is_some());
+}
+
+#[test]
+fn test_multiline_expression_parses_as_one_statement() {
+    let sources = [
+        "let total = 1 +\n    2 *\n    3;",
+        "let total = 1\n    + 2\n    * 3;",
+        "let total = 1 + \\\n    2 * \\  \n    3;",
+    ];
+    for source in sources {
+        let ast = Parser::parse(source).unwrap();
+        assert_eq!(ast.statements.len(), 1, "source: {:?}", source);
+        assert_eq!((ast.statements[0].span.start.line, ast.statements[0].span.end.line), (1, 3));
+    }
+}
+
+#[test]
+fn test_backslash_must_end_the_line() {
+    let err = Parser::parse("let x = 1 \\ + 2;").unwrap_err();
+    assert_eq!(err.message, "Line continuation '\\' must be the last character on its line");
+}
+
+fn parse_let_type(source: &str) -> Option<Type> {
+    let ast = Parser::parse(source).unwrap();
+    match &ast.statements[0].node {
+        Statement::Let { type_annotation, .. } => type_annotation.clone(),
+        _ => panic!("expected let"),
+    }
+}
+
+#[test]
+fn test_let_type_annotations() {
+    assert_eq!(parse_let_type("let x = 1;"), None);
+    assert_eq!(parse_let_type("let x: Number = 1;"), Some(Type::Number));
+    assert_eq!(
+        parse_let_type("let xs: Array<String> = [];"),
+        Some(Type::Array(Box::new(Type::String)))
+    );
+    assert_eq!(
+        parse_let_type("let grid: Array<Array<Number>> = [];"),
+        Some(Type::Array(Box::new(Type::Array(Box::new(Type::Number)))))
+    );
+}
+
+#[test]
+fn test_unknown_type_annotation_is_error() {
+    let err = Parser::parse("let x: Widget = 1;").unwrap_err();
+    assert_eq!(err.message, "Unknown type: Widget");
+    let err = Parser::parse("let xs: Array<Number = [];").unwrap_err();
+    assert_eq!(err.message, "Expected '>' after element type");
+}
+
+#[test]
+fn test_import_statement() {
+    let ast = Parser::parse("import \"lib/math.as\";").unwrap();
+    match &ast.statements[0].node {
+        Statement::Import { path, names } => {
+            assert_eq!(path, "lib/math.as");
+            assert_eq!(names, &None);
+        }
+        _ => panic!("expected import"),
+    }
+}
+
+#[test]
+fn test_import_requires_string_path() {
+    let err = Parser::parse("import math;").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::SyntaxError);
+    assert_eq!(err.message, "Expected string path after import");
+}
+
+#[test]
+fn test_assignment_target_must_be_identifier() {
+    let err = Parser::parse("1 + x = 2;").unwrap_err();
+    assert_eq!(err.message, "Invalid assignment target");
+}
+
+#[test]
+fn test_function_parameter_annotations() {
+    let ast = Parser::parse("fn f(a, b: Array<Number>) { return a; }").unwrap();
+    match &ast.statements[0].node {
+        Statement::Function { params, param_types, return_type, .. } => {
+            assert_eq!(params, &vec!["a".to_string(), "b".to_string()]);
+            assert_eq!(param_types, &vec![None, Some(Type::Array(Box::new(Type::Number)))]);
+            assert_eq!(return_type, &None);
+        }
+        _ => panic!("expected function"),
+    }
+}
+
+#[test]
+fn test_function_return_type_annotation() {
+    let ast = Parser::parse("fn f(a) -> Map<String> { return a; }").unwrap();
+    match &ast.statements[0].node {
+        Statement::Function { return_type, .. } => {
+            assert_eq!(return_type, &Some(Type::Map(Box::new(Type::String))));
+        }
+        _ => panic!("expected function"),
+    }
+}
+
+#[test]
+fn test_named_import_statement() {
+    let ast = Parser::parse("import { add, PI } from \"math.as\";").unwrap();
+    match &ast.statements[0].node {
+        Statement::Import { path, names } => {
+            assert_eq!(path, "math.as");
+            assert_eq!(names, &Some(vec!["add".to_string(), "PI".to_string()]));
+        }
+        _ => panic!("expected import"),
+    }
+    
+    let err = Parser::parse("import { add } \"math.as\";").unwrap_err();
+    assert_eq!(err.message, "Expected 'from' after imported names");
+}
+
+#[test]
+fn test_ast_serializes_to_json() {
+    let ast = Parser::parse("let x = 1 + 2;").unwrap();
+    let json = serde_json::to_value(&ast).unwrap();
+    let statement = &json["statements"][0];
+    
+    assert_eq!(statement["node"]["Let"]["name"], "x");
+    assert_eq!(statement["node"]["Let"]["value"]["BinaryOp"]["operator"], "Add");
+    assert_eq!(statement["node"]["Let"]["value"]["BinaryOp"]["left"]["Number"], 1.0);
+    assert_eq!(statement["span"]["start"]["line"], 1);
+}
+
+#[test]
+fn test_parse_recovering_reports_every_bad_statement() {
+    let (ast, errors) = Parser::parse_recovering("let = 1;\noutput 1;\nlet y = ;\noutput 2;");
+    let found: Vec<(usize, &str)> = errors.iter().map(|e| (e.location.line, e.message.as_str())).collect();
+    
+    assert_eq!(found, vec![(1, "Expected variable name"), (3, "Expected expression, found Semicolon")]);
+    assert_eq!(ast.statements.len(), 2);
+    assert_eq!(ast.statements[1].node, Statement::Output(vec![Expression::Number(2.0)]));
+}
+
+#[test]
+fn test_parse_recovering_skips_whole_braced_statement() {
+    let (ast, errors) = Parser::parse_recovering("fn f() {\n  let = 1;\n  output 3;\n}\noutput 2;\nif true { output ); } let c = 3;");
+    
+    assert_eq!(errors.iter().map(|e| e.location.line).collect::<Vec<_>>(), vec![2, 6]);
+    assert_eq!(ast.statements.len(), 2);
+    assert!(matches!(&ast.statements[1].node, Statement::Let { name, .. } if name == "c"));
+}
+
+#[test]
+fn test_parse_recovering_keeps_statements_before_lexer_error() {
+    let (ast, errors) = Parser::parse_recovering("let a = 1;\nlet b = @;\noutput a;");
+    
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Unexpected character: @");
+    assert_eq!(ast.statements.len(), 1);
+}
+
+#[test]
+fn test_is_incomplete_detects_unfinished_statements() {
+    for source in ["fn f() {", "if x {\n  output 1;", "let x =", "output (1 +", "let s = \"abc", "/* note", "fn f(a,", "let a = [1,\n2,"] {
+        assert!(Parser::is_incomplete(source), "source: {:?}", source);
+    }
+    for source in ["output 1;", "fn f() {\n  return 1;\n}", "output );", "let = 1;", "output 1 }", ""] {
+        assert!(!Parser::is_incomplete(source), "source: {:?}", source);
+    }
+}
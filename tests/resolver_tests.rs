@@ -0,0 +1,108 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+use aslang::resolver::Resolver;
+use std::fs;
+use std::ffi::OsStr;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A directory under the system temp dir, removed when dropped.
+struct TempDir(PathBuf);
+
+impl Deref for TempDir {
+    type Target = Path;
+    
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A fresh directory holding `files`.
+fn temp_dir(name: &str, files: &[&str]) -> TempDir {
+    let dir = std::env::temp_dir().join(format!("aslang-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for file in files {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "output 1;").unwrap();
+    }
+    TempDir(dir.canonicalize().unwrap())
+}
+
+#[test]
+fn test_search_paths_are_tried_in_order() {
+    let first = temp_dir("search-first", &["shared.as"]);
+    let second = temp_dir("search-second", &["shared.as", "only_second.as"]);
+    let mut resolver = Resolver::new();
+    resolver.search_paths = vec![first.join("missing"), first.to_path_buf(), second.to_path_buf()];
+    
+    assert_eq!(resolver.resolve("shared.as", None).unwrap(), first.join("shared.as"));
+    assert_eq!(resolver.resolve("only_second.as", None).unwrap(), second.join("only_second.as"));
+}
+
+#[test]
+fn test_file_next_to_importer_wins_over_search_paths() {
+    let library = temp_dir("search-library", &["util.as"]);
+    let project = temp_dir("search-project", &["main.as", "util.as"]);
+    let mut resolver = Resolver::new();
+    resolver.search_paths = vec![library.to_path_buf()];
+    
+    let resolved = resolver.resolve("util.as", Some(&project.join("main.as"))).unwrap();
+    assert_eq!(resolved, project.join("util.as"));
+}
+
+#[test]
+fn test_missing_module_lists_directories_tried() {
+    let project = temp_dir("search-missing", &["main.as"]);
+    let library = temp_dir("search-missing-library", &[]);
+    let mut resolver = Resolver::new();
+    resolver.search_paths = vec![library.to_path_buf()];
+    
+    let err = resolver.resolve("nope.as", Some(&project.join("main.as"))).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    let tried = [project.join("nope.as"), project.join("nope.as/index.as"), library.join("nope.as"), library.join("nope.as/index.as")];
+    let tried: Vec<String> = tried.iter().map(|path| path.display().to_string()).collect();
+    assert_eq!(err.to_string(), format!("module 'nope.as' not found; tried {}", tried.join(", ")));
+    
+    let err = resolver.resolve("nope", Some(&project.join("main.as"))).unwrap_err();
+    assert!(err.to_string().contains(&format!("{}, ", project.join("nope.as").display())));
+}
+
+#[test]
+fn test_search_paths_split_like_path() {
+    let separator = if cfg!(windows) { ";" } else { ":" };
+    let value = ["/opt/aslang/lib", "", "/usr/share/aslang"].join(separator);
+    
+    assert_eq!(Resolver::split_search_paths(OsStr::new(&value)),
+        vec![PathBuf::from("/opt/aslang/lib"), PathBuf::from("/usr/share/aslang")]);
+    assert!(Resolver::split_search_paths(OsStr::new("")).is_empty());
+}
+
+#[test]
+fn test_extension_is_optional() {
+    let project = temp_dir("extension", &["math.as", "notes"]);
+    let resolver = Resolver::new();
+    let main = project.join("main.as");
+    
+    assert_eq!(resolver.resolve("math", Some(&main)).unwrap(), project.join("math.as"));
+    assert_eq!(resolver.resolve("math.as", Some(&main)).unwrap(), project.join("math.as"));
+    // A file without the extension is still found as written
+    assert_eq!(resolver.resolve("notes", Some(&main)).unwrap(), project.join("notes"));
+}
+
+#[test]
+fn test_directory_import_uses_index() {
+    let project = temp_dir("index", &["geometry/index.as", "geometry/shapes.as"]);
+    let resolver = Resolver::new();
+    let main = project.join("main.as");
+    
+    assert_eq!(resolver.resolve("geometry", Some(&main)).unwrap(), project.join("geometry/index.as"));
+    assert_eq!(resolver.resolve("geometry/shapes", Some(&main)).unwrap(), project.join("geometry/shapes.as"));
+}
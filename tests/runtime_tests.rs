@@ -0,0 +1,1011 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+use aslang::compiler::{CodeObject, Compiler, Constant, Opcode, Program};
+use aslang::parser::Parser;
+use aslang::error::{ASError, ErrorKind, SourceLocation};
+use aslang::runtime::{FunctionRef, Runtime, Value};
+use std::rc::Rc;
+
+#[test]
+fn test_output_single_value() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output 5;").unwrap();
+    
+    assert_eq!(output, "5\n");
+}
+
+#[test]
+fn test_output_multiple_values() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("let a = 1; let b = 2; output a, b;").unwrap();
+    
+    assert_eq!(output, "1 2\n");
+}
+
+#[test]
+fn test_output_multiple_statements() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output \"x\", 1 + 2; output \"y\";").unwrap();
+    
+    assert_eq!(output, "x 3\ny\n");
+}
+
+#[test]
+fn test_runtime_error_reports_source_line() {
+    let source = "let a = 10;\nlet b = 0;\n\nif a > 5 {\n    output a / b;\n}\n";
+    let mut runtime = Runtime::new();
+    let err = runtime.execute(source).unwrap_err();
+    
+    assert_eq!(err.message, "Division by zero");
+    assert_eq!(err.location.line, 5);
+    assert_eq!(err.location.column, 5);
+}
+
+#[test]
+fn test_runtime_error_in_function_reports_its_line() {
+    let source = "fn ratio(a, b) {\n    let scaled = a * 2;\n\n    return scaled / b;\n}\noutput ratio(1, 0);\n";
+    let mut runtime = Runtime::new();
+    let err = runtime.execute(source).unwrap_err();
+    
+    assert_eq!(err.message, "Division by zero");
+    assert_eq!(err.location.line, 4);
+}
+
+#[test]
+fn test_undefined_variable_is_error_by_default() {
+    let mut runtime = Runtime::new();
+    let err = runtime.execute("output missing;").unwrap_err();
+    
+    assert_eq!(err.message, "Undefined variable: missing");
+}
+
+#[test]
+fn test_undefined_variable_is_none_when_lenient() {
+    let mut runtime = Runtime::new();
+    runtime.undefined_is_none = true;
+    let output = runtime.execute("output missing;").unwrap();
+    
+    assert_eq!(output, "none\n");
+}
+
+#[test]
+fn test_trace_disabled_by_default() {
+    let mut runtime = Runtime::new();
+    runtime.execute("output 1;").unwrap();
+    
+    assert!(runtime.trace().is_empty());
+}
+
+#[test]
+fn test_trace_records_last_opcodes_before_error() {
+    let mut runtime = Runtime::new();
+    runtime.set_trace_capacity(3);
+    runtime.execute("let a = 1; let b = 0; output a / b;").unwrap_err();
+    
+    let trace = runtime.trace();
+    assert_eq!(trace.len(), 3);
+    assert_eq!(trace[0].opcode, Opcode::LoadVar("a".to_string()));
+    assert_eq!(trace[2].opcode, Opcode::Divide);
+    assert_eq!(trace[2].stack_depth, 2);
+    assert_eq!(trace[2].stack_top, Some(Value::Integer(0)));
+}
+
+#[test]
+fn test_import_handler_supplies_module_source() {
+    let mut runtime = Runtime::new();
+    runtime.set_import_handler(|path| match path {
+        "greeting" => Ok("let greeting = \"hello\";".to_string()),
+        _ => Err(ASError::new(ErrorKind::IOError, format!("No module {}", path), SourceLocation::new(0, 0))),
+    });
+    let output = runtime.execute("import \"greeting\"; output greeting;").unwrap();
+    
+    assert_eq!(output, "hello\n");
+}
+
+#[test]
+fn test_import_handler_denies_import() {
+    let mut runtime = Runtime::new();
+    runtime.set_import_handler(|path| {
+        Err(ASError::new(ErrorKind::IOError, format!("Import of '{}' denied", path), SourceLocation::new(0, 0)))
+    });
+    let err = runtime.execute("import \"/etc/passwd\";").unwrap_err();
+    
+    assert_eq!(err.kind, ErrorKind::IOError);
+    assert_eq!(err.message, "Import of '/etc/passwd' denied");
+}
+
+#[test]
+fn test_unique_numbers() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output unique([1, 2, 2, 3, 1]);").unwrap();
+    
+    assert_eq!(output, "[1, 2, 3]\n");
+}
+
+#[test]
+fn test_unique_strings_and_mixed_types() {
+    let mut runtime = Runtime::new();
+    // Array literals must be homogeneous, so the mixed array comes from the host
+    let mixed = vec![Value::Integer(1), Value::String("1".to_string()), Value::Integer(1)];
+    runtime.set_global("mixed", Value::Array(mixed));
+    let output = runtime.execute("output unique([\"b\", \"a\", \"b\"]); output unique(mixed);").unwrap();
+    
+    assert_eq!(output, "[b, a]\n[1, 1]\n");
+}
+
+#[test]
+fn test_unsafe_integer_allowed_by_default() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output 9007199254740991 + 1;").unwrap();
+    
+    assert_eq!(output, "9007199254740992\n");
+}
+
+#[test]
+fn test_unsafe_integer_errors_in_strict_mode() {
+    let mut runtime = Runtime::new();
+    runtime.strict_integers = true;
+    
+    assert!(runtime.execute("output 9007199254740990 + 1;").is_ok());
+    let err = runtime.execute("output 9007199254740991 + 1;").unwrap_err();
+    assert_eq!(err.message, "Integer overflow: 9007199254740992 exceeds the safe integer range");
+}
+
+#[test]
+fn test_empty_and_blank_programs() {
+    for source in ["", "   ", "\n\n", "\t\r\n", "// just a comment", "// one\n// two\n"] {
+        let mut runtime = Runtime::new();
+        assert_eq!(runtime.execute(source).unwrap(), "", "source: {:?}", source);
+    }
+}
+
+#[test]
+fn test_shebang_line_is_skipped() {
+    let mut runtime = Runtime::new();
+    
+    assert_eq!(runtime.execute("#!/usr/bin/env aslang").unwrap(), "");
+    assert_eq!(runtime.execute("#!/usr/bin/env aslang\noutput 1;").unwrap(), "1\n");
+}
+
+#[test]
+fn test_expression_statement_keeps_side_effects() {
+    let mut runtime = Runtime::new();
+    let source = "fn doThing() { output \"side\"; return 1; } doThing();";
+    
+    assert_eq!(runtime.execute(source).unwrap(), "side\n");
+}
+
+#[test]
+fn test_bare_expression_statement_is_discarded() {
+    let mut runtime = Runtime::new();
+    
+    assert_eq!(runtime.execute("5;").unwrap(), "");
+    assert_eq!(runtime.execute("5; output 1;").unwrap(), "1\n");
+}
+
+#[test]
+fn test_float_equality_is_exact_by_default() {
+    let mut runtime = Runtime::new();
+    
+    assert_eq!(runtime.execute("output 0.1 + 0.2 == 0.3;").unwrap(), "false\n");
+    assert_eq!(runtime.execute("output 0.1 + 0.2 != 0.3;").unwrap(), "true\n");
+}
+
+#[test]
+fn test_float_epsilon_makes_equality_approximate() {
+    let mut runtime = Runtime::new();
+    runtime.float_epsilon = Some(1e-9);
+    
+    assert_eq!(runtime.execute("output 0.1 + 0.2 == 0.3;").unwrap(), "true\n");
+    assert_eq!(runtime.execute("output 0.1 + 0.2 != 0.3;").unwrap(), "false\n");
+    assert_eq!(runtime.execute("output 1 == 1.1;").unwrap(), "false\n");
+}
+
+#[test]
+fn test_approx_eq_builtin() {
+    let mut runtime = Runtime::new();
+    
+    assert_eq!(runtime.execute("output approx_eq(0.1 + 0.2, 0.3, 0.000001);").unwrap(), "true\n");
+    assert_eq!(runtime.execute("output approx_eq(1, 2, 0.5);").unwrap(), "false\n");
+}
+
+#[test]
+fn test_indexing_bytes_yields_numbers() {
+    let mut runtime = Runtime::new();
+    let source = "let data = base64_decode(\"AP8q\"); output data[0], data[1], data[2];";
+    
+    assert_eq!(runtime.execute(source).unwrap(), "0 255 42\n");
+    let err = runtime.execute("let data = base64_decode(\"AP8q\"); output data[3];").unwrap_err();
+    assert_eq!(err.message, "Index 3 out of bounds for length 3");
+}
+
+#[test]
+fn test_stack_underflow_names_opcode() {
+    let mut main = CodeObject::new("<main>", Vec::new());
+    main.bytecode = vec![Opcode::LoadConst(0), Opcode::Add];
+    main.constants = vec![Constant::Number(1.0)];
+    let mut runtime = Runtime::new();
+    
+    let err = runtime.execute_program(Program { main, functions: Vec::new() }).unwrap_err();
+    assert_eq!(err.message, "Stack underflow at <main>:0001 while executing Add (likely a compiler bug)");
+}
+
+#[test]
+fn test_estimated_memory_grows_with_live_values() {
+    let mut scalar = Runtime::new();
+    scalar.execute("let x = 1;").unwrap();
+    let mut array = Runtime::new();
+    array.execute("let x = [\"a\", \"b\", \"c\", \"a longer string value\"];").unwrap();
+    
+    assert!(scalar.estimated_memory() > 0);
+    assert!(array.estimated_memory() > scalar.estimated_memory());
+}
+
+#[test]
+fn test_memory_limit_aborts_cleanly() {
+    let mut runtime = Runtime::new();
+    runtime.memory_limit = Some(512);
+    let source = "fn grow(s) { return grow(s + s); } grow(\"x\");";
+    
+    let err = runtime.execute(source).unwrap_err();
+    assert_eq!(err.kind, ErrorKind::RuntimeError);
+    assert!(err.message.starts_with("Memory limit exceeded"), "{}", err.message);
+}
+
+#[test]
+fn test_memory_limit_catches_growth_without_calls() {
+    let mut runtime = Runtime::new();
+    runtime.memory_limit = Some(4096);
+    
+    let err = runtime.execute("let items = {\"a\": \"entry\"};\nlet i = 0;\nwhile true {\n    items[str(i)] = \"entry\";\n    i = i + 1;\n}").unwrap_err();
+    assert!(err.message.starts_with("Memory limit exceeded"), "{}", err.message);
+    assert_eq!(err.location.line, 4);
+    let err = runtime.execute("let s = \"x\"; while true { s = s + s; }").unwrap_err();
+    assert!(err.message.starts_with("Memory limit exceeded"), "{}", err.message);
+    // Arithmetic alone never trips the limit
+    let mut runtime = Runtime::new();
+    runtime.memory_limit = Some(4096);
+    assert_eq!(runtime.execute("let n = 0; while n < 1000 { n = n + 1; } output n;").unwrap(), "1000\n");
+}
+
+#[test]
+fn test_negative_indices_count_from_end() {
+    let mut runtime = Runtime::new();
+    
+    assert_eq!(runtime.execute("let arr = [10, 20, 30]; output arr[-1], arr[-3];").unwrap(), "30 10\n");
+    assert_eq!(runtime.execute("output \"hello\"[-2];").unwrap(), "l\n");
+    let err = runtime.execute("let arr = [10, 20, 30]; output arr[-4];").unwrap_err();
+    assert_eq!(err.message, "Index -4 out of bounds for length 3");
+}
+
+#[test]
+fn test_set_index_accepts_negative_index() {
+    let mut main = CodeObject::new("<main>", Vec::new());
+    main.bytecode = vec![
+        Opcode::LoadConst(0),
+        Opcode::LoadConst(1),
+        Opcode::MakeArray(2),
+        Opcode::LoadConst(2),
+        Opcode::LoadConst(3),
+        Opcode::SetIndex,
+        Opcode::Output(true),
+    ];
+    main.constants = vec![
+        Constant::Number(1.0),
+        Constant::Number(2.0),
+        Constant::Number(-1.0),
+        Constant::String(Rc::from("last")),
+    ];
+    let mut runtime = Runtime::new();
+    
+    assert_eq!(runtime.execute_program(Program { main, functions: Vec::new() }).unwrap(), "[1, last]\n");
+}
+
+/// Records the address of every buffer written, to see where text comes from.
+#[derive(Clone, Default)]
+struct WriteAddresses(Rc<std::cell::RefCell<Vec<usize>>>);
+
+impl std::io::Write for WriteAddresses {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().push(buf.as_ptr() as usize);
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_repeated_string_output_writes_pooled_literal() {
+    let source = "fn say(n) { if n > 0 { output \"same\"; say(n - 1); } } say(500); say(500);";
+    let ast = Parser::parse(source).unwrap();
+    let program = Compiler::new().compile(&ast).unwrap();
+    let say = &program.functions[0];
+    let index = say.constants.iter().position(|c| *c == Constant::String(Rc::from("same"))).unwrap();
+    assert!(say.bytecode.contains(&Opcode::OutputStr(index, true)));
+    let literal = match &say.constants[index] {
+        Constant::String(s) => s.as_ptr() as usize,
+        other => panic!("expected a string constant, got {:?}", other),
+    };
+    
+    let addresses = WriteAddresses::default();
+    let mut runtime = Runtime::new();
+    runtime.set_output_writer(addresses.clone());
+    let output = runtime.execute_program(program).unwrap();
+    
+    assert_eq!(output, "same\n".repeat(1000));
+    // Every iteration wrote the pooled text itself, never a copy of it
+    let from_pool = addresses.0.borrow().iter().filter(|&&address| address == literal).count();
+    assert_eq!(from_pool, 1000);
+}
+
+#[test]
+fn test_constant_pool_preserves_program_output() {
+    let mut runtime = Runtime::new();
+    let source = "let a = 2; let b = 2; output a + b, \"x\", \"x\", true, true, 2;";
+    
+    assert_eq!(runtime.execute(source).unwrap(), "4 x x true true 2\n");
+}
+
+#[test]
+fn test_input_is_string_by_default() {
+    let mut runtime = Runtime::new();
+    runtime.set_input_handler(|_| Ok("42\n".to_string()));
+    
+    assert_eq!(runtime.execute("input x; output x + \"!\";").unwrap(), "42!\n");
+}
+
+#[test]
+fn test_numeric_input_parses_numbers() {
+    let mut runtime = Runtime::new();
+    runtime.numeric_input = true;
+    let mut lines = vec!["hello", "42"].into_iter();
+    runtime.set_input_handler(move |_| Ok(lines.next().unwrap_or_default().to_string()));
+    
+    assert_eq!(runtime.execute("input x; input y; output x, y + 1;").unwrap(), "hello 43\n");
+}
+
+#[test]
+fn test_format_builtin_in_output() {
+    let mut runtime = Runtime::new();
+    
+    assert_eq!(runtime.execute("let pi = 3.14159; output format(\"{:.2}\", pi);").unwrap(), "3.14\n");
+    let err = runtime.execute("output format(\"{}\");").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::RuntimeError);
+}
+
+#[test]
+fn test_set_global_binds_variable_before_execute() {
+    let mut runtime = Runtime::new();
+    runtime.set_global("n", Value::Number(20.0));
+    
+    assert_eq!(runtime.execute("output n * 2 + 2;").unwrap(), "42\n");
+}
+
+#[test]
+fn test_set_global_is_type_checked() {
+    let mut runtime = Runtime::new();
+    runtime.set_global("name", Value::String("as".to_string()));
+    
+    let err = runtime.execute("let x: Number = name;").unwrap_err();
+    assert_eq!(err.message, "Type mismatch: expected Number, got String");
+}
+
+#[test]
+fn test_multiline_expression_evaluates_once() {
+    let mut runtime = Runtime::new();
+    
+    assert_eq!(runtime.execute("output 1 +\n  2 * \\\n  3;").unwrap(), "7\n");
+}
+
+const JUMPY_PROGRAM: &str = "let i = 0;
+while i < 3 {
+    if i == 1 { output \"one\"; } elseif i == 2 { output \"two\"; } else { output i; }
+    i = i + 1;
+}";
+
+fn compile_relative(source: &str) -> Program {
+    let ast = Parser::parse(source).unwrap();
+    let mut compiler = Compiler::new();
+    compiler.relative_jumps = true;
+    compiler.compile(&ast).unwrap()
+}
+
+#[test]
+fn test_relative_jumps_execute_identically() {
+    let expected = Runtime::new().execute(JUMPY_PROGRAM).unwrap();
+    let program = compile_relative(JUMPY_PROGRAM);
+    assert!(program.main.bytecode.iter().any(|op| matches!(op, Opcode::JumpRel(_))));
+    assert!(!program.main.bytecode.iter().any(|op| matches!(op, Opcode::Jump(_) | Opcode::JumpIfFalse(_))));
+    
+    assert_eq!(Runtime::new().execute_program(program).unwrap(), expected);
+    assert_eq!(expected, "0\none\ntwo\n");
+}
+
+#[test]
+fn test_relative_jumps_survive_relocation() {
+    let mut program = compile_relative(JUMPY_PROGRAM);
+    // Shift the code three opcode pairs further into the buffer
+    let padding: Vec<Opcode> = (0..3).flat_map(|_| [Opcode::LoadConst(0), Opcode::Pop]).collect();
+    program.main.bytecode.splice(0..0, padding.iter().cloned());
+    program.main.lines.splice(0..0, padding.iter().map(|_| SourceLocation::new(0, 0)));
+    
+    assert_eq!(Runtime::new().execute_program(program).unwrap(), "0\none\ntwo\n");
+}
+
+#[test]
+fn test_relative_jumps_short_circuit() {
+    let program = compile_relative("output false and 1 / 0 > 0, true or 1 / 0 > 0;");
+    assert!(program.main.bytecode.iter().any(|op| matches!(op, Opcode::JumpIfTrueRel(_))));
+    assert_eq!(Runtime::new().execute_program(program).unwrap(), "false true\n");
+}
+
+#[test]
+fn test_linked_import_runs_module_once() {
+    let mut runtime = Runtime::new();
+    runtime.link_imports = true;
+    runtime.set_import_handler(|path| match path {
+        "util" => Ok("output \"loaded\"; fn double(n) { return n * 2; }".to_string()),
+        _ => Err(ASError::new(ErrorKind::IOError, format!("No module {}", path), SourceLocation::new(0, 0))),
+    });
+    let output = runtime.execute("import \"util\"; import \"util\"; output double(4);").unwrap();
+    
+    assert_eq!(output, "loaded\n8\n");
+    // A second copy of `double` would be reported as a redeclaration
+    assert!(runtime.warnings().is_empty());
+}
+
+/// Runs `source` with imports served from `modules`, returning everything
+/// written, imported modules' output included.
+fn run_with_modules(source: &str, modules: &'static [(&'static str, &'static str)]) -> String {
+    let buffer = SharedBuffer::default();
+    let mut runtime = Runtime::new();
+    runtime.set_output_writer(buffer.clone());
+    runtime.set_import_handler(move |path| match modules.iter().find(|(name, _)| *name == path) {
+        Some((_, source)) => Ok(source.to_string()),
+        None => Err(ASError::new(ErrorKind::IOError, format!("No module {}", path), SourceLocation::new(0, 0))),
+    });
+    runtime.execute(source).unwrap();
+    let written = buffer.0.borrow().clone();
+    String::from_utf8(written).unwrap()
+}
+
+#[test]
+fn test_circular_imports_terminate() {
+    let modules = &[
+        ("a", "output \"a\"; import \"b\";"),
+        ("b", "output \"b\"; import \"a\";"),
+    ];
+    
+    assert_eq!(run_with_modules("import \"a\"; output \"main\";", modules), "a\nb\nmain\n");
+}
+
+#[test]
+fn test_module_imported_twice_runs_once() {
+    let modules = &[
+        ("left", "import \"shared\"; output \"left\";"),
+        ("right", "import \"shared\"; output \"right\";"),
+        ("shared", "output \"shared\";"),
+    ];
+    
+    assert_eq!(run_with_modules("import \"left\"; import \"right\"; import \"shared\";", modules), "shared\nleft\nright\n");
+}
+
+const MATH_MODULE: &[(&str, &str)] = &[(
+    "math",
+    "let PI = 3; let secret = 1; let count = 0;
+fn area(r) { return PI * r * r; }
+fn bump() { count = count + 1; return count; }
+fn helper() { return secret; }",
+)];
+
+#[test]
+fn test_named_import_binds_only_requested_names() {
+    let source = "let secret = 99; import { area, PI, bump } from \"math\"; output area(2), PI, secret, bump(), bump();";
+    
+    assert_eq!(run_with_modules(source, MATH_MODULE), "12 3 99 1 2\n");
+}
+
+#[test]
+fn test_named_import_hides_other_names() {
+    let mut runtime = Runtime::new();
+    runtime.set_import_handler(|_| Ok(MATH_MODULE[0].1.to_string()));
+    
+    let err = runtime.execute("import { area } from \"math\"; output PI;").unwrap_err();
+    assert_eq!(err.message, "Undefined variable: PI");
+    let err = runtime.execute("import { area } from \"math\"; output helper();").unwrap_err();
+    assert_eq!(err.message, "Function 'helper' not defined or supported in this runtime version");
+}
+
+#[test]
+fn test_named_import_of_undefined_name_is_runtime_error() {
+    let mut runtime = Runtime::new();
+    runtime.set_import_handler(|_| Ok(MATH_MODULE[0].1.to_string()));
+    let err = runtime.execute("import { area, tau } from \"math\";").unwrap_err();
+    
+    assert_eq!(err.kind, ErrorKind::RuntimeError);
+    assert_eq!(err.message, "Module 'math' does not define 'tau'");
+}
+
+#[test]
+fn test_function_value_display() {
+    let mut runtime = Runtime::new();
+    assert_eq!(runtime.execute("fn add(a, b) { return a + b; } output add;").unwrap(), "<function add arity=2>\n");
+    
+    let anonymous = Value::Function(FunctionRef { index: 0, name: None, arity: 1 });
+    assert_eq!(anonymous.to_string(), "<function anonymous arity=1>");
+}
+
+#[test]
+fn test_function_values_compare_by_identity() {
+    let mut runtime = Runtime::new();
+    let source = "fn a() { return 1; } fn b() { return 1; } output a == a, a == b, a != b;";
+    
+    assert_eq!(runtime.execute(source).unwrap(), "true false true\n");
+}
+
+#[test]
+fn test_function_used_as_number_is_type_error() {
+    let mut runtime = Runtime::new();
+    let err = runtime.execute("fn a() { return 1; } output a + 1;").unwrap_err();
+    
+    assert_eq!(err.kind, ErrorKind::TypeError);
+    assert_eq!(err.message, "Cannot apply Add to fn() -> Any and Number");
+}
+
+#[test]
+fn test_coverage_reports_untaken_branch() {
+    let mut runtime = Runtime::new();
+    runtime.coverage = true;
+    let source = "let x = 1;\n\n// comment\nif x > 5 {\n    output \"big\";\n} else {\n    output \"small\";\n}\n";
+    runtime.execute(source).unwrap();
+    
+    let covered: Vec<usize> = runtime.covered_lines().iter().copied().collect();
+    let coverable: Vec<usize> = runtime.coverable_lines().iter().copied().collect();
+    assert_eq!(coverable, vec![1, 4, 5, 7]);
+    assert_eq!(covered, vec![1, 4, 7]);
+}
+
+#[test]
+fn test_char_values() {
+    let mut runtime = Runtime::new();
+    
+    assert_eq!(runtime.execute("let c: Char = 'x'; output c, c == 'x', c == \"x\";").unwrap(), "x true false\n");
+}
+
+#[test]
+fn test_string_interpolation() {
+    let mut runtime = Runtime::new();
+    
+    assert_eq!(runtime.execute("let x = 41; output \"x is ${x + 1}!\";").unwrap(), "x is 42!\n");
+    assert_eq!(runtime.execute("output \"${\"}\"}${[1, 2][0]} \\${x}\";").unwrap(), "}1 ${x}\n");
+}
+
+#[test]
+fn test_unterminated_interpolation_is_syntax_error() {
+    let mut runtime = Runtime::new();
+    let err = runtime.execute("output \"x is ${x + 1\";").unwrap_err();
+    
+    assert_eq!(err.kind, ErrorKind::SyntaxError);
+    assert_eq!(err.message, "Unterminated string interpolation");
+}
+
+#[test]
+fn test_assignment_updates_variable() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("let x = 1; x = 5; output x;").unwrap();
+    assert_eq!(output, "5\n");
+    let output = runtime.execute("let a = 0; let b = 0; a = b = 7; output a, b;").unwrap();
+    assert_eq!(output, "7 7\n");
+}
+
+#[test]
+fn test_assignment_to_undeclared_variable_is_error() {
+    let mut runtime = Runtime::new();
+    let err = runtime.execute("y = 5;").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::TypeError);
+    assert_eq!(err.message, "Cannot assign to undeclared variable 'y'");
+}
+
+#[test]
+fn test_compound_assignment() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("let x = 5; x += 3; output x;").unwrap();
+    assert_eq!(output, "8\n");
+    let output = runtime.execute("let y = 10; y -= 4; y *= 3; y /= 2; output y;").unwrap();
+    assert_eq!(output, "9\n");
+}
+
+#[test]
+fn test_modulo() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output 17 % 5; output 7.5 % 2; output 1 + 10 % 4 * 2;").unwrap();
+    assert_eq!(output, "2\n1.5\n5\n");
+    let err = runtime.execute("output 3 % 0;").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::RuntimeError);
+    assert_eq!(err.message, "Modulo by zero");
+}
+
+#[test]
+fn test_power() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output 2 ^ 10; output 2 ^ 3 ^ 2; output 3 * 2 ^ 2;").unwrap();
+    assert_eq!(output, "1024\n512\n12\n");
+}
+
+#[test]
+fn test_power_binds_tighter_than_unary_minus() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output -2 ^ 2; output (-2) ^ 2; output 2 ^ -1; output -2 * 3;").unwrap();
+    assert_eq!(output, "-4\n4\n0.5\n-6\n");
+}
+
+#[test]
+fn test_double_star_power() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output 2 ** 3; output 2 ** 3 ** 2; let x = 3; x *= 2; output x;").unwrap();
+    assert_eq!(output, "8\n512\n6\n");
+}
+
+#[test]
+fn test_comparison_operators() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute(
+        "output 1 != 2, 2 != 2, \"a\" != \"b\";\
+         output 1 <= 2, 2 <= 2, 3 <= 2;\
+         output 1 >= 2, 2 >= 2, 3 >= 2;\
+         output 1<2, 2>1;",
+    ).unwrap();
+    assert_eq!(output, "true false true\ntrue true false\nfalse true true\ntrue true\n");
+}
+
+#[test]
+fn test_logical_operators() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute(
+        "output true and true, true and false, false or true, false or false; output 1 < 2 && 2 < 3, 1 > 2 || 2 > 3;",
+    ).unwrap();
+    assert_eq!(output, "true false true false\ntrue false\n");
+}
+
+#[test]
+fn test_logical_operators_short_circuit() {
+    let mut runtime = Runtime::new();
+    // The right operand would divide by zero if it were evaluated
+    let output = runtime.execute("output false and 1 / 0 > 0; output true or 1 / 0 > 0;").unwrap();
+    assert_eq!(output, "false\ntrue\n");
+    let err = runtime.execute("output true and 1 / 0 > 0;").unwrap_err();
+    assert_eq!(err.message, "Division by zero");
+}
+
+#[test]
+fn test_not_operators() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output !true, not false, not (1 > 2); output ~0, ~5, ~-1;").unwrap();
+    assert_eq!(output, "false true true\n-1 -6 0\n");
+    let err = runtime.execute("output ~1.5;").unwrap_err();
+    assert_eq!(err.message, "Bitwise not requires an integer, got 1.5");
+}
+
+#[test]
+fn test_not_on_non_boolean_is_runtime_error() {
+    let mut runtime = Runtime::new();
+    runtime.set_global("n", Value::None);
+    let err = runtime.execute("output not n;").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::RuntimeError);
+    assert_eq!(err.message, "Cannot apply 'not' to none");
+}
+
+#[test]
+fn test_break_exits_while_loop() {
+    let mut runtime = Runtime::new();
+    let source = "let i = 0;\nwhile true {\n    if i == 3 { break; }\n    output i;\n    i += 1;\n}\noutput \"done\";";
+    assert_eq!(runtime.execute(source).unwrap(), "0\n1\n2\ndone\n");
+}
+
+#[test]
+fn test_continue_skips_to_next_iteration() {
+    let mut runtime = Runtime::new();
+    let source = "let i = 0;\nwhile i < 5 {\n    i += 1;\n    if i % 2 == 0 { continue; }\n    output i;\n}";
+    assert_eq!(runtime.execute(source).unwrap(), "1\n3\n5\n");
+    let source = "for (let j = 0; j < 5; j += 1) {\n    if j == 1 { continue; }\n    if j == 3 { break; }\n    output j;\n}";
+    assert_eq!(runtime.execute(source).unwrap(), "0\n2\n");
+}
+
+#[test]
+fn test_break_outside_loop_is_error() {
+    let mut runtime = Runtime::new();
+    let err = runtime.execute("let x = 1;\n\n  break;").unwrap_err();
+    assert_eq!(err.message, "'break' outside of a loop");
+    assert_eq!((err.location.line, err.location.column), (3, 3));
+    let err = runtime.execute("while true {\n    fn f() { continue; } break; }").unwrap_err();
+    assert_eq!(err.message, "'continue' outside of a loop");
+    assert_eq!((err.location.line, err.location.column), (2, 14));
+}
+
+#[test]
+fn test_function_call_returns_value() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("fn add(a, b) { return a + b } output add(2, 3)").unwrap();
+    assert_eq!(output, "5\n");
+}
+
+#[test]
+fn test_recursive_function() {
+    let mut runtime = Runtime::new();
+    let source = "fn fact(n) {\n    if n <= 1 { return 1; }\n    return n * fact(n - 1);\n}\noutput fact(5);";
+    assert_eq!(runtime.execute(source).unwrap(), "120\n");
+}
+
+#[test]
+fn test_function_locals_do_not_leak() {
+    let mut runtime = Runtime::new();
+    let source = "let y = 1;\nfn f(x) { let y = x * 10; return y; }\noutput f(2), y;";
+    assert_eq!(runtime.execute(source).unwrap(), "20 1\n");
+}
+
+#[test]
+fn test_index_assignment_mutates_array() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("let a = [1, 2, 3]; output a[1]; a[0] = 9; a[-1] = 7; output a;").unwrap();
+    assert_eq!(output, "2\n[9, 2, 7]\n");
+    let err = runtime.execute("let a = [1, 2, 3]; a[3] = 0;").unwrap_err();
+    assert_eq!(err.message, "Index 3 out of bounds for length 3");
+    let err = runtime.execute("let a = [1, 2, 3]; a[0.5] = 0;").unwrap_err();
+    assert_eq!(err.message, "Invalid index: 0.5");
+}
+
+#[test]
+fn test_index_assignment_is_not_a_value() {
+    let mut runtime = Runtime::new();
+    let err = runtime.execute("let a = [1]; output a[0] = 2;").unwrap_err();
+    assert_eq!(err.message, "Index assignment cannot be used as a value");
+}
+
+#[test]
+fn test_crate_root_reexports_the_runtime() {
+    // `lib.rs` re-exports the one runtime, so both paths name the same types
+    let value: aslang::Value = Value::Number(1.0);
+    assert_eq!(value, Value::Number(1.0));
+    assert_eq!(aslang::execute("output 1 + 1;").unwrap(), "2\n");
+    let _: aslang::Runtime = Runtime::new();
+}
+
+/// A writer the test can read back after handing it to the runtime.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<std::cell::RefCell<Vec<u8>>>);
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Records each write separately, to see when output arrives.
+#[derive(Clone, Default)]
+struct WriteLog(Rc<std::cell::RefCell<Vec<String>>>);
+
+impl std::io::Write for WriteLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().push(String::from_utf8(buf.to_vec()).unwrap());
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_output_writer_receives_loop_output_as_it_runs() {
+    let log = WriteLog::default();
+    let mut runtime = Runtime::new();
+    runtime.set_output_writer(log.clone());
+    
+    let output = runtime.execute("let i = 0; while i < 3 { output i; i = i + 1; }").unwrap();
+    assert_eq!(output, "0\n1\n2\n");
+    assert_eq!(*log.0.borrow(), vec!["0\n", "1\n", "2\n"]);
+}
+
+#[test]
+fn test_output_writer_captures_output() {
+    let buffer = SharedBuffer::default();
+    let mut runtime = Runtime::new();
+    runtime.set_output_writer(buffer.clone());
+    
+    let output = runtime.execute("output \"hi\"; output 1, 2; print(3);").unwrap();
+    assert_eq!(output, "hi\n1 2\n3\n");
+    assert_eq!(String::from_utf8(buffer.0.borrow().clone()).unwrap(), output);
+}
+
+#[test]
+fn test_input_reads_scripted_lines_in_order() {
+    let mut lines = vec!["Ada", "Grace"].into_iter();
+    let mut runtime = Runtime::new();
+    runtime.set_input_handler(move |_| Ok(lines.next().unwrap_or_default().to_string()));
+    
+    let source = "input \"First: \" a; input \"Second: \" b; output a, b;";
+    assert_eq!(runtime.execute(source).unwrap(), "First: Second: Ada Grace\n");
+}
+
+#[test]
+fn test_input_handler_error_propagates() {
+    let mut runtime = Runtime::new();
+    runtime.set_input_handler(|_| Err(ASError::new(ErrorKind::RuntimeError, "no more input".to_string(), SourceLocation::new(0, 0))));
+    let err = runtime.execute("input x;").unwrap_err();
+    assert_eq!(err.message, "no more input");
+}
+
+#[test]
+fn test_integer_arithmetic_stays_integral() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output 2 * 3, 7 / 2, 10 / 2, 7 % 3, 2 ^ 62, -5, 1 + 0.5;").unwrap();
+    assert_eq!(output, "6 3.5 5 1 4611686018427387904 -5 1.5\n");
+}
+
+#[test]
+fn test_integer_overflow_promotes_to_float() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output 2 ^ 62 * 4;").unwrap();
+    assert_eq!(output, "18446744073709552000\n");
+}
+
+#[test]
+fn test_integers_equal_matching_floats() {
+    let mut runtime = Runtime::new();
+    let output = runtime.execute("output 10 / 2 == 5, [1, 2, 3][4 / 2], 3 < 3.5;").unwrap();
+    assert_eq!(output, "true 3 true\n");
+}
+
+#[test]
+fn test_map_literal_get_and_set() {
+    let mut runtime = Runtime::new();
+    let source = "let m = {\"a\": 1, b: 2}; output m[\"a\"]; m[\"c\"] = 3; output m, {};";
+    assert_eq!(runtime.execute(source).unwrap(), "1\n{a: 1, b: 2, c: 3} {}\n");
+    let err = runtime.execute("let m = {\"a\": 1}; output m[\"z\"];").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::RuntimeError);
+    assert_eq!(err.message, "Key 'z' not found");
+}
+
+#[test]
+fn test_len_builtin() {
+    let mut runtime = Runtime::new();
+    assert_eq!(runtime.execute("let n: Number = len([1, 2, 3]); output n, len(\"abc\");").unwrap(), "3 3\n");
+    let err = runtime.execute("output len(5);").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::RuntimeError);
+}
+
+#[test]
+fn test_converted_input_supports_arithmetic() {
+    let mut lines = vec!["20", "1.5"].into_iter();
+    let mut runtime = Runtime::new();
+    runtime.set_input_handler(move |_| Ok(lines.next().unwrap_or_default().to_string()));
+    
+    let source = "input a; input b; output to_number(a) * to_number(b), to_string(1 + 1) + \"!\";";
+    assert_eq!(runtime.execute(source).unwrap(), "30 2!\n");
+}
+
+#[test]
+fn test_instruction_budget_stops_infinite_loop() {
+    let mut runtime = Runtime::new();
+    runtime.set_instruction_limit(Some(1000));
+    
+    let err = runtime.execute("while true { }").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::RuntimeError);
+    assert_eq!(err.message, "Instruction budget exceeded: executed 1000 opcodes");
+    // The budget is per execution, not cumulative
+    assert_eq!(runtime.execute("output 1;").unwrap(), "1\n");
+    runtime.set_instruction_limit(None);
+    assert!(runtime.execute("let i = 0; while i < 1000 { i = i + 1; }").is_ok());
+}
+
+#[test]
+fn test_unbounded_recursion_hits_call_depth_limit() {
+    let mut runtime = Runtime::new();
+    let err = runtime.execute("fn f() { return f() } f();").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::RuntimeError);
+    assert_eq!(err.message, "Maximum call depth of 1000 exceeded");
+    
+    runtime.set_max_call_depth(10);
+    let source = "fn down(n) { if n == 0 { return 0; } return down(n - 1); }";
+    assert!(runtime.execute(&format!("{} output down(9);", source)).is_ok());
+    let err = runtime.execute(&format!("{} output down(10);", source)).unwrap_err();
+    assert_eq!(err.message, "Maximum call depth of 10 exceeded");
+}
+
+#[test]
+fn test_folded_program_matches_unoptimized_output() {
+    let source = "output 2 + 3 * 4, 7 / 2, 2 ^ 10, 10 % 3, -(4 - 6), \"x\" + \"y\", 1 < 2 and 3 >= 3;";
+    let expected = Runtime::new().execute(source).unwrap();
+    let mut compiler = Compiler::new();
+    compiler.optimize = true;
+    let program = compiler.compile(&Parser::parse(source).unwrap()).unwrap();
+    
+    assert_eq!(Runtime::new().execute_program(program).unwrap(), expected);
+}
+
+#[test]
+fn test_inner_let_does_not_overwrite_outer_variable() {
+    let mut runtime = Runtime::new();
+    let source = "let x = 1; if true { let x = 2; output x; } output x;";
+    
+    assert_eq!(runtime.execute(source).unwrap(), "2\n1\n");
+    assert_eq!(runtime.execute("let x = 1; fn f() { let x = 3; return x; } output f(), x;").unwrap(), "3 1\n");
+}
+
+#[test]
+fn test_block_variable_is_not_visible_after_block() {
+    let mut runtime = Runtime::new();
+    let err = runtime.execute("if true { let y = 1; } output y;").unwrap_err();
+    assert_eq!(err.message, "Undefined variable: y");
+    
+    let err = runtime.execute("fn f(a) { let b = a; return b; } output f(1), a;").unwrap_err();
+    assert_eq!(err.message, "Undefined variable: a");
+}
+
+#[test]
+fn test_function_assigns_global_and_parameters_do_not_collide() {
+    let mut runtime = Runtime::new();
+    let source = "let total = 0;
+fn add(n) { total = total + n; return n; }
+fn twice(n) { return add(n) + add(n); }
+output twice(5), total;";
+    
+    assert_eq!(runtime.execute(source).unwrap(), "10 10\n");
+}
+
+/// A directory under the system temp dir, removed when dropped.
+struct TempDir(std::path::PathBuf);
+
+impl std::ops::Deref for TempDir {
+    type Target = std::path::Path;
+    
+    fn deref(&self) -> &std::path::Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Writes `files` (relative path, source) under a fresh temp directory.
+fn write_modules(name: &str, files: &[(&str, &str)]) -> TempDir {
+    let dir = std::env::temp_dir().join(format!("aslang-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    for (path, source) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, source).unwrap();
+    }
+    TempDir(dir)
+}
+
+#[test]
+fn test_relative_imports_resolve_next_to_importing_file() {
+    let dir = write_modules("relative-imports", &[
+        ("app/main.as", "import \"lib/helper.as\"; output twice(limit);"),
+        ("app/lib/helper.as", "import \"./util.as\"; fn twice(n) { return n * 2; }"),
+        ("app/lib/util.as", "let limit = 21;"),
+    ]);
+    let main = dir.join("app/main.as");
+    let source = std::fs::read_to_string(&main).unwrap();
+    
+    for link_imports in [false, true] {
+        let buffer = SharedBuffer::default();
+        let mut runtime = Runtime::new();
+        runtime.set_output_writer(buffer.clone());
+        runtime.link_imports = link_imports;
+        runtime.current_file = Some(main.clone());
+        runtime.execute(&source).unwrap();
+        assert_eq!(String::from_utf8(buffer.0.borrow().clone()).unwrap(), "42\n");
+    }
+}
@@ -0,0 +1,276 @@
+// Copyright (c) 2026 Ashutosh Sharma. All rights reserved.
+
+use aslang::parser::Parser;
+use aslang::types::TypeChecker;
+
+fn check(source: &str) -> Result<(), String> {
+    let ast = Parser::parse(source).unwrap();
+    TypeChecker::new().check(&ast).map_err(|e| e.message)
+}
+
+#[test]
+fn test_index_array_yields_element_type() {
+    assert!(check("let a = [1, 2, 3]; let x: Number = a[0];").is_ok());
+    assert_eq!(
+        check("let a = [1, 2, 3]; let x: String = a[0];"),
+        Err("Type mismatch: expected String, got Number".to_string())
+    );
+}
+
+#[test]
+fn test_index_string_yields_string() {
+    assert!(check("let s = \"abc\"; let c: String = s[1];").is_ok());
+}
+
+#[test]
+fn test_index_number_is_error() {
+    assert_eq!(
+        check("let n = 5; output n[0];"),
+        Err("Cannot index into Number".to_string())
+    );
+}
+
+#[test]
+fn test_non_number_index_is_error() {
+    assert_eq!(
+        check("let a = [1, 2]; output a[\"x\"];"),
+        Err("Index must be Number, got String".to_string())
+    );
+}
+
+#[test]
+fn test_loop_body_changing_condition_variable_type_is_error() {
+    assert_eq!(
+        check("let i = 0; while i < 3 { i = \"done\"; }"),
+        Err("Type mismatch: cannot assign String to 'i' of type Number".to_string())
+    );
+    // A `let` in the body declares a new variable, leaving the condition's alone
+    assert!(check("let i = 0; while i < 3 { let i = \"done\"; i = \"again\"; }").is_ok());
+}
+
+#[test]
+fn test_loop_body_keeping_condition_variable_type_is_ok() {
+    assert!(check("let i = 0; while i < 3 { let i = i + 1; let label = \"x\"; }").is_ok());
+}
+
+#[test]
+fn test_interpolated_string_is_string() {
+    assert!(check("let n = 3; let s: String = \"n = ${n * 2}\";").is_ok());
+    assert_eq!(
+        check("let n = 3; let s: Number = \"n = ${n}\";"),
+        Err("Type mismatch: expected Number, got String".to_string())
+    );
+}
+
+#[test]
+fn test_array_annotation_checks_elements() {
+    assert!(check("let xs: Array<Number> = [1, 2];").is_ok());
+    assert_eq!(
+        check("let xs: Array<Number> = [\"a\"];"),
+        Err("Type mismatch: expected Array<Number>, got Array<String>".to_string())
+    );
+}
+
+#[test]
+fn test_assignment_must_match_declared_type() {
+    assert!(check("let x = 1; x = 2;").is_ok());
+    assert_eq!(
+        check("let x = 1; x = \"two\";"),
+        Err("Type mismatch: cannot assign String to 'x' of type Number".to_string())
+    );
+}
+
+#[test]
+fn test_power_of_numbers_is_number() {
+    assert!(check("let x: Number = 2 ^ 8;").is_ok());
+    assert_eq!(
+        check("let x = \"a\" ^ 2;"),
+        Err("Cannot apply Power to String and Number".to_string())
+    );
+}
+
+#[test]
+fn test_function_parameters_are_in_scope() {
+    assert!(check("fn f(x) { let y = x + 1; return y; }").is_ok());
+    assert_eq!(
+        check("fn f(x) { let y = 1; } output y;"),
+        Err("Undefined variable: y".to_string())
+    );
+}
+
+#[test]
+fn test_index_assignment_checks_element_type() {
+    assert!(check("let a = [1, 2]; a[0] = 5;").is_ok());
+    assert_eq!(
+        check("let a = [1, 2]; a[0] = \"x\";"),
+        Err("Type mismatch: cannot assign String to an element of 'a' of type Number".to_string())
+    );
+}
+
+#[test]
+fn test_map_literal_types() {
+    assert!(check("let m: Map<Number> = {\"a\": 1}; let x: Number = m[\"a\"];").is_ok());
+    assert!(check("let m: Map<String> = {};").is_ok());
+    assert_eq!(
+        check("let m = {\"a\": 1}; output m[0];"),
+        Err("Map key must be String, got Number".to_string())
+    );
+    assert_eq!(
+        check("let m: Map<String> = {\"a\": 1};"),
+        Err("Type mismatch: expected Map<String>, got Map<Number>".to_string())
+    );
+}
+
+#[test]
+fn test_map_values_must_share_a_type() {
+    assert_eq!(
+        check("let m = {\"a\": 1, \"b\": \"x\"}; let n: Number = m[\"b\"]; output n + 1;"),
+        Err("Map values must share a type: value for 'b' is String, expected Number".to_string())
+    );
+    assert!(check("let m = {\"a\": [], \"b\": [1]}; let first: Number = m[\"b\"][0];").is_ok());
+    assert_eq!(
+        check("let m = {\"a\": 1, \"b\": 2}; let s: String = m[\"b\"];"),
+        Err("Type mismatch: expected String, got Number".to_string())
+    );
+}
+
+#[test]
+fn test_math_builtins_return_number() {
+    assert!(check("let x: Number = sqrt(2) + max(1, 2);").is_ok());
+    assert_eq!(
+        check("let s: String = floor(2.5);"),
+        Err("Type mismatch: expected String, got Number".to_string())
+    );
+}
+
+#[test]
+fn test_builtin_arguments_are_checked() {
+    assert_eq!(
+        check("output sqrt(\"x\");"),
+        Err("Type mismatch: argument 1 of 'sqrt' expects Number, got String".to_string())
+    );
+    assert_eq!(check("output min(1);"), Err("Function 'min' expects 2 argument(s), got 1".to_string()));
+    assert_eq!(
+        check("output to_number(5);"),
+        Err("Type mismatch: argument 1 of 'to_number' expects String, got Number".to_string())
+    );
+    // Untyped values and the variadic `format` pass
+    assert!(check("fn f(x) { return sqrt(x); } output format(\"{} {}\", 1, \"a\");").is_ok());
+    // A user function shadowing a built-in uses its own signature
+    assert!(check("fn min(a) { return a; } output min(1);").is_ok());
+}
+
+#[test]
+fn test_string_builtin_signatures() {
+    assert!(check("let parts: Array<String> = split(\"a b\", \" \"); let s: String = join(parts, \",\");").is_ok());
+    assert_eq!(
+        check("let n: Number = upper(\"a\");"),
+        Err("Type mismatch: expected Number, got String".to_string())
+    );
+}
+
+#[test]
+fn test_grouping_keeps_inner_type() {
+    assert!(check("let x: Number = -(4 - 6);").is_ok());
+    assert_eq!(
+        check("let s: String = (1 + 2);"),
+        Err("Type mismatch: expected String, got Number".to_string())
+    );
+}
+
+#[test]
+fn test_call_argument_count_is_checked() {
+    assert!(check("fn add(a, b) { return a + b; } output add(1, 2);").is_ok());
+    assert_eq!(
+        check("fn add(a, b) { return a + b; } output add(1);"),
+        Err("Function 'add' expects 2 argument(s), got 1".to_string())
+    );
+}
+
+#[test]
+fn test_call_argument_types_are_checked() {
+    let source = "fn greet(name: String, times: Number) { return name; }";
+    assert!(check(&format!("{} output greet(\"a\", 2);", source)).is_ok());
+    assert_eq!(
+        check(&format!("{} output greet(\"a\", \"b\");", source)),
+        Err("Type mismatch: argument 2 of 'greet' expects Number, got String".to_string())
+    );
+    // Annotated parameters are typed inside the body
+    assert_eq!(
+        check("fn f(n: Number) { let s: String = n; }"),
+        Err("Type mismatch: expected String, got Number".to_string())
+    );
+}
+
+#[test]
+fn test_homogeneous_array_elements_unify() {
+    assert!(check("let a: Array<String> = [\"x\", \"y\", \"z\"];").is_ok());
+    assert!(check("let a: Array<Array<Number>> = [[], [1, 2], [3]];").is_ok());
+    assert_eq!(
+        check("let a: Array<String> = [[], [1]];"),
+        Err("Type mismatch: expected Array<String>, got Array<Array<Number>>".to_string())
+    );
+    assert!(check("let a: Array<Number> = [];").is_ok());
+}
+
+#[test]
+fn test_heterogeneous_array_is_error() {
+    assert_eq!(
+        check("let a = [1, \"x\", true];"),
+        Err("Array elements must share a type: element 1 is String, expected Number".to_string())
+    );
+    assert_eq!(
+        check("let a = [[1], [\"x\"]];"),
+        Err("Array elements must share a type: element 1 is Array<String>, expected Array<Number>".to_string())
+    );
+}
+
+#[test]
+fn test_return_matches_declared_type() {
+    assert!(check("fn f() -> Number { return 1; } let n: Number = f();").is_ok());
+    assert_eq!(
+        check("fn f() -> Number { return \"x\"; }"),
+        Err("Type mismatch: function returns Number, got String".to_string())
+    );
+    assert_eq!(
+        check("fn f() -> String { return \"x\"; } let n: Number = f();"),
+        Err("Type mismatch: expected Number, got String".to_string())
+    );
+}
+
+#[test]
+fn test_bare_return_needs_void_function() {
+    assert_eq!(
+        check("fn f(n) -> Number { if n > 0 { return; } return n; }"),
+        Err("Missing return value: function returns Number".to_string())
+    );
+    assert!(check("fn f() -> Void { return; }").is_ok());
+    // Unannotated functions keep returning anything
+    assert!(check("fn f(n) { if n > 0 { return; } return \"x\"; }").is_ok());
+}
+
+#[test]
+fn test_underscore_digit_names_are_variables() {
+    assert!(check("let _1 = 3; let _2_0 = _1 + 1; output _2_0;").is_ok());
+    assert_eq!(check("output _100;"), Err("Invalid number: _100".to_string()));
+    
+    let err = TypeChecker::new().check(&Parser::parse("let x = 1;\nlet y = _100 + x;").unwrap()).unwrap_err();
+    assert_eq!(err.kind, aslang::error::ErrorKind::SyntaxError);
+    assert_eq!(err.location.line, 2);
+}
+
+#[test]
+fn test_nested_functions_cannot_see_enclosing_locals() {
+    assert_eq!(
+        check("if true { let y = 1; fn h() { return y; } output h(); }"),
+        Err("Undefined variable: y".to_string())
+    );
+    assert_eq!(
+        check("fn outer() { let z = 5; fn inner() { return z; } return inner(); } output outer();"),
+        Err("Undefined variable: z".to_string())
+    );
+    // Globals and the function's own parameters stay visible
+    assert!(check("let g = 1; fn outer(a) { fn inner(b) { return b + g; } return inner(a); } output outer(2);").is_ok());
+    // The enclosing scope is back once the body is checked
+    assert!(check("fn outer() { let z = 5; fn inner() { return 1; } return z + inner(); } output outer();").is_ok());
+}